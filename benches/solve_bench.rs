@@ -9,8 +9,8 @@ extern crate picross_solver;
 use crate::picross_solver::display::*;
 use crate::picross_solver::solver::*;
 
-fn solve_unit_brut_force(picross_string : & String) {
-    let mut picross = PicrossBoard::picross_from_clue_string(picross_string);
+fn solve_unit_brut_force(picross_string : & str) {
+    let mut picross = PicrossBoard::picross_from_clue_string(picross_string).unwrap();
     solve_picross_board(&mut picross , LineSolveMethod::BrutForce);
 }
 
@@ -19,7 +19,7 @@ fn group_step_brut_force(group : &mut BenchmarkGroup<WallTime>, n : usize, clues
     group.bench_with_input(
         BenchmarkId::new(format!("Picross {}x{}", n, n), clues),
         &clues,
-        |b, s| { b.iter(|| solve_unit_brut_force(&s));});
+        |b, s| { b.iter(|| solve_unit_brut_force(s));});
 }
 
 fn solve_fixed_bench_brut_force(c: &mut Criterion) {
@@ -39,8 +39,8 @@ fn solve_fixed_bench_brut_force(c: &mut Criterion) {
     group_step_brut_force(&mut group, 25, & CLUE_STRING_25X25.to_string());
 }
 
-fn solve_unit_space_distribution(picross_string : & String) {
-    let mut picross = PicrossBoard::picross_from_clue_string(picross_string);
+fn solve_unit_space_distribution(picross_string : & str) {
+    let mut picross = PicrossBoard::picross_from_clue_string(picross_string).unwrap();
     solve_picross_board(&mut picross , LineSolveMethod::SpaceDistribution);
 }
 
@@ -49,7 +49,7 @@ fn group_step_space_distribution(group : &mut BenchmarkGroup<WallTime>, n : usiz
     group.bench_with_input(
         BenchmarkId::new(format!("Picross {}x{}", n, n), clues),
         &clues,
-        |b, s| { b.iter(|| solve_unit_space_distribution(&s));});
+        |b, s| { b.iter(|| solve_unit_space_distribution(s));});
 }
 
 fn solve_fixed_bench_space_distribution(c: &mut Criterion) {
@@ -70,7 +70,50 @@ fn solve_fixed_bench_space_distribution(c: &mut Criterion) {
     group_step_space_distribution(&mut group, 30, & CLUE_STRING_30X30.to_string());
 }
 
-criterion_group!(benches, solve_fixed_bench_brut_force, solve_fixed_bench_space_distribution);
+fn solve_unit_dynamic(picross_string : & str) {
+    let mut picross = PicrossBoard::picross_from_clue_string(picross_string).unwrap();
+    solve_picross_board(&mut picross , LineSolveMethod::Dynamic);
+}
+
+fn group_step_dynamic(group : &mut BenchmarkGroup<WallTime>, n : usize, clues : & String) {
+    group.throughput(Throughput::Elements(n as u64));
+    group.bench_with_input(
+        BenchmarkId::new(format!("Picross {}x{}", n, n), clues),
+        &clues,
+        |b, s| { b.iter(|| solve_unit_dynamic(s));});
+}
+
+fn solve_fixed_bench_dynamic(c: &mut Criterion) {
+    let mut group = c.benchmark_group("fixed picross dynamic");
+
+    group_step_dynamic(&mut group, 5, & CLUE_STRING_5X5.to_string());
+    group_step_dynamic(&mut group, 6, & CLUE_STRING_6X6.to_string());
+    group_step_dynamic(&mut group, 7, & CLUE_STRING_7X7.to_string());
+    group_step_dynamic(&mut group, 8, & CLUE_STRING_8X8.to_string());
+    group_step_dynamic(&mut group, 9, & CLUE_STRING_9X9.to_string());
+    group_step_dynamic(&mut group, 10, & CLUE_STRING_10X10.to_string());
+    group_step_dynamic(&mut group, 12, & CLUE_STRING_12X12.to_string());
+    group_step_dynamic(&mut group, 15, & CLUE_STRING_15X15.to_string());
+    group_step_dynamic(&mut group, 16, & CLUE_STRING_16X16.to_string());
+    group_step_dynamic(&mut group, 20, & CLUE_STRING_20X20.to_string());
+    group_step_dynamic(&mut group, 25, & CLUE_STRING_25X25.to_string());
+    group_step_dynamic(&mut group, 30, & CLUE_STRING_30X30.to_string());
+}
+
+fn line_validation_bench(c: &mut Criterion) {
+    let mut group = c.benchmark_group("line validation");
+
+    for &size in [30, 300, 3000].iter() {
+        let img = create_random_image_board(1, size, 7);
+        let clues = PicrossBoard::new_from_image(&img).left.get_line_copy(0);
+        let packed = PackedLine::from_image_line(&img.get_row(0));
+        group.throughput(Throughput::Elements(size as u64));
+        group.bench_with_input(BenchmarkId::new("From image line", size), &size, |b, _| { b.iter(|| validate_line(&clues, &img.get_row(0)));});
+        group.bench_with_input(BenchmarkId::new("Packed", size), &size, |b, _| { b.iter(|| validate_packed_line(&clues, &packed));});
+    }
+}
+
+criterion_group!(benches, solve_fixed_bench_brut_force, solve_fixed_bench_space_distribution, solve_fixed_bench_dynamic, line_validation_bench);
 criterion_main!(benches);
 
 
@@ -5,4 +5,6 @@ use image::*;
 pub mod clue_board;
 pub mod clue_format_parser;
 
-pub use crate::clues::{clue_board::*, clue_format_parser::*};
\ No newline at end of file
+pub use crate::clues::{clue_board::*, clue_format_parser::*};
+//The .non reader and writer build boards, they are written with the picross types and served from here
+pub use crate::picross::non_format::{self, *};
\ No newline at end of file
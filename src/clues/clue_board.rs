@@ -5,9 +5,11 @@ use std::cmp;
 pub type ClueArray = Vec<Vec<usize>>;
 pub type ClueLine = Vec<usize>;
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClueBoard {
     orientation: Orientation,
+    #[cfg_attr(feature = "serde", serde(rename = "clues"))]
     values: ClueArray,
 }
 
@@ -30,20 +32,20 @@ impl ClueBoard {
         if orientation == Orientation::Vertical {
             let mut values = vec![ Vec::with_capacity(0); image.height()];
             let mut sum : usize = 0;
-            for y in 0..image.height() {
+            for (y, line) in values.iter_mut().enumerate() {
                 for x in 0..image.width() {
                     match image.get_value(x, y) {
                         CaseState::ON => sum += 1,
                         _ => {
                             if sum > 0 {
-                                values[y].push(sum);
+                                line.push(sum);
                                 sum = 0;
                             }
                         },
                     }
                 }
                 if sum > 0 {
-                    values[y].push(sum);
+                    line.push(sum);
                     sum = 0;
                 }
             }
@@ -54,20 +56,20 @@ impl ClueBoard {
         } else {
             let mut values = vec![ Vec::with_capacity(0); image.width()];
             let mut sum : usize = 0;
-            for x in 0..image.width() {
+            for (x, line) in values.iter_mut().enumerate() {
                 for y in 0..image.height() {
                     match image.get_value(x, y) {
                         CaseState::ON => sum += 1,
                         _ => {
                             if sum > 0 {
-                                values[x].push(sum);
+                                line.push(sum);
                                 sum = 0;
                             }
                         },
                     }
                 }
                 if sum > 0 {
-                    values[x].push(sum);
+                    line.push(sum);
                     sum = 0;
                 }
             }
@@ -101,12 +103,22 @@ impl ClueBoard {
     }
 
     pub fn get_line(&self, idx : usize) -> &ClueLine {
-        return &self.values[idx];
+        &self.values[idx]
     }
 
     pub fn get_line_copy(&self, idx : usize) -> ClueLine {
-        return self.values[idx].to_vec();
+        self.values[idx].to_vec()
     }
+
+    pub fn get_clue_array(&self) -> &ClueArray {
+        &self.values
+    }
+}
+
+//Cells taken by the blocks of a line and the gaps between them, None when it overflows
+pub fn get_min_line_size(clues : & [usize]) -> Option<usize> {
+    let blocks = clues.iter().filter(|&&c| c > 0);
+    blocks.clone().try_fold(blocks.count().saturating_sub(1), |size, &block| size.checked_add(block))
 }
 
 #[allow(dead_code)]
@@ -1,61 +1,136 @@
 use super::*;
 
-pub fn clue_array_from_substring(clue_string : &mut String) -> ClueArray{
-    let group_delimiter : &[_] = &[',', '.'];
-    let group_strings : Vec<&str> = clue_string[1..].split(group_delimiter).collect();
-    let mut values = vec![ Vec::<usize>::with_capacity(0); group_strings.len()];
-    for (i, group_string) in group_strings.iter().enumerate() {
-        let mut long_number = String::from("");
-        let mut is_in_parenthesis = false;
-        for c in group_string.chars() {
-            match c {
-                '0'..='9' => {
-                    if is_in_parenthesis {
-                        long_number.push_str(&c.to_string());
-                    } else {
-                        values[i].push(c.to_digit(10).unwrap() as usize);
-                    }
-                }, '(' => {
-                    is_in_parenthesis = true;
-                }, ')' => {
-                    is_in_parenthesis = false;
-                    values[i].push(long_number.parse().unwrap());
-                    long_number.clear();
-                },_ => {
-                    println!("Not valid value as input")
-                },
-            }
+use std::error::Error;
+use std::fmt;
+
+//Positions are byte offsets in the whole clue string
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ClueParseError {
+    UnexpectedCharacter(usize, char),
+    //Position of the opening parenthesis
+    UnclosedParenthesis(usize),
+    MissingTop,
+    MissingLeft,
+    //Parentheses with no digit inside
+    EmptyNumber(usize),
+    //Position of the opening parenthesis of a number that does not fit in usize
+    NumberTooLarge(usize),
+}
+
+impl fmt::Display for ClueParseError {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ClueParseError::UnexpectedCharacter(pos, c) => write!(f, "unexpected character '{}' at {}", c, pos),
+            ClueParseError::UnclosedParenthesis(pos) => write!(f, "parenthesis opened at {} is not closed", pos),
+            ClueParseError::MissingTop => write!(f, "no top section (T or U)"),
+            ClueParseError::MissingLeft => write!(f, "no left section (L or S)"),
+            ClueParseError::EmptyNumber(pos) => write!(f, "no number between the parentheses at {}", pos),
+            ClueParseError::NumberTooLarge(pos) => write!(f, "number between the parentheses at {} is too large", pos),
+        }
+    }
+}
+
+impl Error for ClueParseError {}
+
+//The section starts with its letter at offset in the clue string
+fn clue_array_from_section(section : &str, offset : usize) -> Result<ClueArray, ClueParseError> {
+    let mut values = vec![ Vec::<usize>::with_capacity(0) ];
+    let mut long_number = String::from("");
+    let mut parenthesis_pos : Option<usize> = None;
+    for (i, c) in section.char_indices().skip(1) {
+        let pos = offset + i;
+        match (c, parenthesis_pos) {
+            ('0'..='9', Some(_)) => {
+                long_number.push(c);
+            }, ('0'..='9', None) => {
+                values.last_mut().unwrap().push(c.to_digit(10).unwrap() as usize);
+            }, ('(', None) => {
+                parenthesis_pos = Some(pos);
+            }, (')', Some(open_pos)) => {
+                if long_number.is_empty() {
+                    return Err(ClueParseError::EmptyNumber(open_pos));
+                }
+                //Only digits were pushed, only an overflow can fail
+                let number = long_number.parse().map_err(|_| ClueParseError::NumberTooLarge(open_pos))?;
+                values.last_mut().unwrap().push(number);
+                long_number.clear();
+                parenthesis_pos = None;
+            }, (',', None) | ('.', None) => {
+                values.push(Vec::with_capacity(0));
+            }, _ => {
+                return Err(ClueParseError::UnexpectedCharacter(pos, c));
+            },
         }
     }
-    values
+    match parenthesis_pos {
+        Some(open_pos) => Err(ClueParseError::UnclosedParenthesis(open_pos)),
+        None => Ok(values),
+    }
 }
 
-pub fn clue_boards_from_clue_string( clue_string : & String) -> (ClueArray, ClueArray){
+pub fn clue_array_from_substring(clue_string : &str) -> Result<ClueArray, ClueParseError> {
+    clue_array_from_section(clue_string, 0)
+}
+
+pub fn clue_boards_from_clue_string( clue_string : & str) -> Result<(ClueArray, ClueArray), ClueParseError> {
     let top_delimiter : &[_] = &['t', 'T', 'u', 'U'];
-    let top_start_pos = clue_string.find(top_delimiter);        
+    let top_start_pos = clue_string.find(top_delimiter);
     let left_delimiter : &[_] = &['l', 'L', 's', 'S'];
     let left_start_pos = clue_string.find(left_delimiter);
 
-    let left_pos :usize = left_start_pos.unwrap();
-    let top_pos :usize = top_start_pos.unwrap();
+    let top_pos :usize = top_start_pos.ok_or(ClueParseError::MissingTop)?;
+    let left_pos :usize = left_start_pos.ok_or(ClueParseError::MissingLeft)?;
+
+    //Nothing is allowed before the first section
+    if let Some(c) = clue_string.chars().next() {
+        if top_pos != 0 && left_pos != 0 {
+            return Err(ClueParseError::UnexpectedCharacter(0, c));
+        }
+    }
 
     if top_pos < left_pos {
         let (top_sub_string, left_sub_string) = clue_string.split_at(left_pos);
-        (clue_array_from_substring(&mut String::from(top_sub_string)), clue_array_from_substring(&mut String::from(left_sub_string)))
+        Ok((clue_array_from_section(top_sub_string, top_pos)?, clue_array_from_section(left_sub_string, left_pos)?))
     } else {
         let (left_sub_string, top_sub_string) = clue_string.split_at(top_pos);
-        (clue_array_from_substring(&mut String::from(top_sub_string)), clue_array_from_substring(&mut String::from(left_sub_string)))
+        Ok((clue_array_from_section(top_sub_string, top_pos)?, clue_array_from_section(left_sub_string, left_pos)?))
     }
 }
 
+//Empty lines are written as 0, clues of more than one digit between parentheses
+pub fn clue_substring_from_clue_array(section : char, values : & ClueArray) -> String {
+    let mut clue_string = String::new();
+    clue_string.push(section);
+    for (i, line) in values.iter().enumerate() {
+        if i > 0 {
+            clue_string.push(',');
+        }
+        if line.is_empty() {
+            clue_string.push('0');
+        }
+        for clue in line.iter() {
+            if *clue > 9 {
+                clue_string.push_str(&format!("({})", clue));
+            } else {
+                clue_string.push_str(&clue.to_string());
+            }
+        }
+    }
+    clue_string
+}
+
+pub fn clue_string_from_clue_boards(top : & ClueArray, left : & ClueArray) -> String {
+    clue_substring_from_clue_array('T', top) + &clue_substring_from_clue_array('L', left)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn test_clue_string_decomposition() {
-        let mut input = "L123(45)6789,(10)T987(54)321".to_string();
-        let (top, left) = clue_boards_from_clue_string(& mut input);
+        let input = "L123(45)6789,(10)T987(54)321".to_string();
+        let (top, left) = clue_boards_from_clue_string(&input).unwrap();
         assert_eq!(left[0][0], 1);
         assert_eq!(left[0][3], 45);
         assert_eq!(left[0][left[0].len()-1], 9);
@@ -66,4 +141,57 @@ mod tests {
         assert_eq!(top[0][top[0].len()-1], 1);
     }
 
-}
\ No newline at end of file
+    #[test]
+    fn test_clue_string_errors() {
+        assert_eq!(clue_boards_from_clue_string("L1,2"), Err(ClueParseError::MissingTop));
+        assert_eq!(clue_boards_from_clue_string("T1,2"), Err(ClueParseError::MissingLeft));
+        assert_eq!(clue_boards_from_clue_string("T1,2x3L1"), Err(ClueParseError::UnexpectedCharacter(4, 'x')));
+        assert_eq!(clue_boards_from_clue_string("T1,(12L1"), Err(ClueParseError::UnclosedParenthesis(3)));
+        assert_eq!(clue_boards_from_clue_string("L1,2T1,()"), Err(ClueParseError::EmptyNumber(7)));
+        assert_eq!(clue_boards_from_clue_string("T1)L1"), Err(ClueParseError::UnexpectedCharacter(2, ')')));
+        assert_eq!(clue_boards_from_clue_string(" T1L1"), Err(ClueParseError::UnexpectedCharacter(0, ' ')));
+        assert_eq!(clue_array_from_substring("T1,((2)"), Err(ClueParseError::UnexpectedCharacter(4, '(')));
+        assert_eq!(clue_boards_from_clue_string("T(99999999999999999999999)L1"), Err(ClueParseError::NumberTooLarge(1)));
+        assert_eq!(ClueParseError::NumberTooLarge(1).to_string(), "number between the parentheses at 1 is too large");
+    }
+
+    #[test]
+    fn test_clue_string_empty_lines() {
+        let (top, left) = clue_boards_from_clue_string("T0,,1L").unwrap();
+        assert_eq!(top, vec![vec![0], vec![], vec![1]]);
+        assert_eq!(left, vec![Vec::<usize>::new()]);
+    }
+
+    #[test]
+    fn test_clue_string_serialization() {
+        let top = vec![vec![1, 10], vec![], vec![0], vec![123, 4, 5]];
+        let left = vec![vec![2], vec![11]];
+        let clue_string = clue_string_from_clue_boards(&top, &left);
+        assert_eq!(clue_string, "T1(10),0,0,(123)45L2,(11)");
+
+        let (parsed_top, parsed_left) = clue_boards_from_clue_string(&clue_string).unwrap();
+        assert_eq!(parsed_top, vec![vec![1, 10], vec![0], vec![0], vec![123, 4, 5]]);
+        assert_eq!(parsed_left, left);
+    }
+
+    #[test]
+    fn test_clue_string_round_trip() {
+        for seed in 0..200 {
+            let image = create_random_image_board(1 + (seed as usize) % 30, 1 + (seed as usize * 7) % 40, seed);
+            let top_board = ClueBoard::new_from_image(Orientation::Horizontal, &image);
+            let left_board = ClueBoard::new_from_image(Orientation::Vertical, &image);
+            let top = top_board.get_clue_array();
+            let left = left_board.get_clue_array();
+            let clue_string = clue_string_from_clue_boards(top, left);
+
+            //Only the empty lines change, they come back as a single 0
+            let normalize = |values : & ClueArray| -> ClueArray {
+                values.iter().map(|line| if line.is_empty() { vec![0] } else { line.clone() }).collect()
+            };
+            let (parsed_top, parsed_left) = clue_boards_from_clue_string(&clue_string).unwrap();
+            assert_eq!(parsed_top, normalize(top));
+            assert_eq!(parsed_left, normalize(left));
+            assert_eq!(clue_string_from_clue_boards(&parsed_top, &parsed_left), clue_string);
+        }
+    }
+}
@@ -9,4 +9,6 @@ pub mod display_picross;
 pub mod display_context;
 pub mod display_row_col_picross;
 pub mod display_picross_line_unit;
-pub use crate::display::{displayable::*, display_clue::*, display_image::*, display_picross::*, display_context::*, display_row_col_picross::*};
\ No newline at end of file
+pub mod display_ambiguity;
+pub mod display_solve_animation;
+pub use crate::display::{displayable::*, display_clue::*, display_image::*, display_context::*, display_ambiguity::*, display_row_col_picross::*, display_solve_animation::*};
\ No newline at end of file
@@ -0,0 +1,35 @@
+use super::*;
+
+use std::io::{stdout, Write};
+use crossterm::{ queue, style::{self, SetForegroundColor, ResetColor, Color, Colorize} };
+
+pub const AMBIGUOUS_ON_COLOR : Color = Color::DarkRed;
+pub const AMBIGUOUS_OFF_COLOR : Color = Color::Red;
+
+//The reference solution, with the cells that differ in another solution highlighted
+impl Displayable for AmbiguityReport {
+
+    fn display_in_context(&self, context : & DisplayContext) {
+        let mut stdout = stdout();
+        for y in 0..self.reference.height() {
+            for x in 0..self.reference.width() {
+                match (self.reference.get_value(x,y), self.is_ambiguous(x,y)) {
+                    (CaseState::ON, true) => queue!(stdout, SetForegroundColor(AMBIGUOUS_ON_COLOR), style::PrintStyledContent( ON_STR.dark_red() ), ResetColor).ok(),
+                    (_, true) => queue!(stdout, SetForegroundColor(AMBIGUOUS_OFF_COLOR), style::PrintStyledContent( OFF_STR.red() ), ResetColor).ok(),
+                    (CaseState::UNKNOWN, false) => queue!(stdout, SetForegroundColor(UNKNOWN_COLOR), style::PrintStyledContent( UNKNOWN_STR.dark_grey() ) , ResetColor).ok(),
+                    (CaseState::ON, false) => queue!(stdout, SetForegroundColor(ON_COLOR), style::PrintStyledContent( ON_STR.black() ), ResetColor).ok(),
+                    (CaseState::OFF, false) => queue!(stdout, SetForegroundColor(OFF_COLOR), style::PrintStyledContent( OFF_STR.white() ), ResetColor).ok(),
+                };
+            }
+            self.go_to_next_line(context);
+        }
+    }
+
+    fn display_height(&self) -> u16 {
+        self.reference.height() as u16
+    }
+
+    fn display_width(&self) -> u16 {
+        (self.reference.width() * CELL_SIZE) as u16
+    }
+}
@@ -8,7 +8,7 @@ impl Displayable for PicrossBoard {
     fn display_in_context(&self, context : & DisplayContext) {
         let mut stdout = stdout();
 
-        let mut local_context = context.clone();
+        let mut local_context = *context;
         
         //Draw top
         local_context.left_offset = self.left.display_width();
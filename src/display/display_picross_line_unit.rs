@@ -4,10 +4,10 @@ use std::io::{stdout, Write};
 use crossterm::{ queue, style::{self, SetForegroundColor, ResetColor, Colorize}};
 
 
-pub fn display_line(values : &Vec<CaseState>) {
+pub fn display_line(values : &[CaseState]) {
     let mut stdout = stdout();
-    for i in 0..values.len() {
-        match values[i] {
+    for value in values {
+        match value {
             CaseState::UNKNOWN => queue!(stdout, SetForegroundColor(UNKNOWN_COLOR), style::PrintStyledContent( UNKNOWN_STR.grey() ) , ResetColor).ok(),
             CaseState::ON => queue!(stdout, SetForegroundColor(ON_COLOR), style::PrintStyledContent( ON_STR.black() ), ResetColor).ok(),
             CaseState::OFF => queue!(stdout, SetForegroundColor(OFF_COLOR), style::PrintStyledContent( OFF_STR.white() ), ResetColor).ok(),
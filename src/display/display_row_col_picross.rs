@@ -1,13 +1,34 @@
 use super::*;
 
 use std::io::{stdout, Write};
-use crossterm::{ queue, style::{self, SetForegroundColor, ResetColor, Colorize}, cursor };
+use crossterm::{ queue, style::{self, SetForegroundColor, ResetColor, Color, Colorize}, cursor };
 use std::cmp;
 
-fn get_max_clue_size(lines : & Vec<PicrossLineUnit>) -> usize {
-    let mut max = lines[0].clues.len();
+pub const SOLVING_LINE_COLOR : Color = Color::DarkYellow;
+pub const DECIDED_ON_COLOR : Color = Color::DarkGreen;
+pub const DECIDED_OFF_COLOR : Color = Color::Green;
+
+//Cells drawn in other colors, to follow the solve
+#[derive(Clone, Default, Debug)]
+pub struct SolveHighlight {
+    pub line: Option<(Orientation, usize)>,
+    pub decided: Vec<(usize, usize)>,
+}
+
+impl SolveHighlight {
+    fn is_in_line(&self, x : usize, y : usize) -> bool {
+        match self.line {
+            Some((Orientation::Horizontal, idx)) => y == idx,
+            Some((Orientation::Vertical, idx)) => x == idx,
+            None => false,
+        }
+    }
+}
+
+fn get_max_clue_size(lines : & [ClueLine]) -> usize {
+    let mut max = lines[0].len();
     for line in lines[1..].iter() {
-        max = cmp::max(max, line.clues.len());
+        max = cmp::max(max, line.len());
     }
     cmp::max(max, 1)
 }
@@ -15,35 +36,35 @@ fn get_max_clue_size(lines : & Vec<PicrossLineUnit>) -> usize {
 impl RowColPicross {
 
     fn top_clues_display_height(&self) -> u16 {
-        get_max_clue_size(&self.cols) as u16
+        get_max_clue_size(&self.col_clues) as u16
     }
 
     fn top_clues_display_width(&self) -> u16 {
-        (self.cols.len() * EMPTY_STR_HORIZONTAL.len()) as u16
+        (self.width() * EMPTY_STR_HORIZONTAL.len()) as u16
     }
 
     fn left_clues_display_height(&self) -> u16 {
-        self.rows.len() as u16
+        self.height() as u16
     }
 
     fn left_clues_display_width(&self) -> u16 {
-        (get_max_clue_size(&self.rows) * EMPTY_STR_VERTICAL.len()) as u16
+        (get_max_clue_size(&self.row_clues) * EMPTY_STR_VERTICAL.len()) as u16
     }
 
     fn display_top_clues(&self, context : & DisplayContext) {
         let mut stdout = stdout();
-        let  max_size =  get_max_clue_size(&self.cols);
+        let  max_size =  get_max_clue_size(&self.col_clues);
         
         for i in (0..max_size).rev() {
             queue!(stdout, cursor::MoveRight(context.left_offset)).ok();
-            for j in 0..self.cols.len() {
-                let height = self.cols[j].clues.len();
+            for j in 0..self.width() {
+                let height = self.col_clues[j].len();
                 if (height == 0) && i == 0 {
                     queue!(stdout, style::Print( format!("{:>2}", 0))).ok();
                 } else if i > (height-1) {
                         queue!(stdout, style::Print( EMPTY_STR_HORIZONTAL )).ok();
                 } else {
-                        queue!(stdout, style::Print( format!("{:>2}", self.cols[j].clues[height - 1 - i]))).ok();
+                        queue!(stdout, style::Print( format!("{:>2}", self.col_clues[j][height - 1 - i]))).ok();
                 }
             }
     
@@ -53,10 +74,10 @@ impl RowColPicross {
 
     fn display_left_clues(&self, context : & DisplayContext) {
         let mut stdout = stdout();
-        let max_size = get_max_clue_size(&self.rows);
+        let max_size = get_max_clue_size(&self.row_clues);
     
-        for i in 0..self.rows.len() {
-            let empty = max_size - self.rows[i].clues.len();
+        for i in 0..self.height() {
+            let empty = max_size - self.row_clues[i].len();
     
             if empty == max_size {
                 for _j in 0..(empty-1) {
@@ -70,8 +91,8 @@ impl RowColPicross {
                     queue!(stdout, style::Print( EMPTY_STR_VERTICAL )).ok();
                 }
         
-                for j in 0..self.rows[i].clues.len(){
-                    queue!(stdout, style::Print( format!("{:>3}", self.rows[i].clues[j]))).ok();
+                for j in 0..self.row_clues[i].len(){
+                    queue!(stdout, style::Print( format!("{:>3}", self.row_clues[i][j]))).ok();
                 }
             }
     
@@ -79,11 +100,15 @@ impl RowColPicross {
         }
     }
 
-    fn display_image(&self, context : & DisplayContext){
+    fn display_image(&self, context : & DisplayContext, highlight : & SolveHighlight){
         let mut stdout = stdout();
-        for y in 0..self.rows.len() {
-            for x in 0..self.rows[0].values.len() {
-                match self.rows[y].values[x] {
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                let decided = highlight.decided.contains(&(x, y));
+                match self.get_value(x, y) {
+                    CaseState::ON if decided => queue!(stdout, SetForegroundColor(DECIDED_ON_COLOR), style::PrintStyledContent( ON_STR.dark_green() ), ResetColor).ok(),
+                    CaseState::OFF if decided => queue!(stdout, SetForegroundColor(DECIDED_OFF_COLOR), style::PrintStyledContent( OFF_STR.green() ), ResetColor).ok(),
+                    CaseState::UNKNOWN if highlight.is_in_line(x, y) => queue!(stdout, SetForegroundColor(SOLVING_LINE_COLOR), style::PrintStyledContent( UNKNOWN_STR.dark_yellow() ), ResetColor).ok(),
                     CaseState::UNKNOWN => queue!(stdout, SetForegroundColor(UNKNOWN_COLOR), style::PrintStyledContent( UNKNOWN_STR.grey() ) , ResetColor).ok(),
                     CaseState::ON => queue!(stdout, SetForegroundColor(ON_COLOR), style::PrintStyledContent( ON_STR.black() ), ResetColor).ok(),
                     CaseState::OFF => queue!(stdout, SetForegroundColor(OFF_COLOR), style::PrintStyledContent( OFF_STR.white() ), ResetColor).ok(),
@@ -93,13 +118,10 @@ impl RowColPicross {
             queue!(stdout, cursor::MoveRight(context.left_offset)).ok();
         }
     }
-}
-
 
-impl Displayable for RowColPicross {
-    fn display_in_context(&self, context : & DisplayContext) {
+    pub fn display_highlighted_in_context(&self, context : & DisplayContext, highlight : & SolveHighlight) {
         let mut stdout = stdout();
-        let mut local_context = context.clone();
+        let mut local_context = *context;
     
         //Draw top
         local_context.left_offset = self.left_clues_display_width();
@@ -115,11 +137,18 @@ impl Displayable for RowColPicross {
         //Draw image
         local_context.draw_inplace = true;
         local_context.left_offset = self.left_clues_display_width();
-        self.display_image(&local_context);
+        self.display_image(&local_context, highlight);
 
         //Back to line
         self.go_to_next_line(context);
     }
+}
+
+
+impl Displayable for RowColPicross {
+    fn display_in_context(&self, context : & DisplayContext) {
+        self.display_highlighted_in_context(context, &SolveHighlight::default());
+    }
 
     fn display_width(&self) -> u16 {
         self.left_clues_display_width() + self.top_clues_display_width()
@@ -0,0 +1,106 @@
+use super::*;
+
+use std::io::{stdout, Write};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use crossterm::{ queue, cursor };
+
+//Redraws the board in place after every line solve, with the line solved and its new cells highlighted
+pub struct SolveAnimation {
+    pub delay: Duration,
+    board: RowColPicross,
+    //Boards before each guess of the search, restored when the branch is left
+    branches: Vec<ImageBoard>,
+    drawn: bool,
+}
+
+impl SolveAnimation {
+    pub fn new(picross : & RowColPicross, delay : Duration) -> Self {
+        let mut board = picross.clone();
+        board.observer = None;
+        board.trace = None;
+        Self {
+            delay,
+            board,
+            branches: Vec::new(),
+            drawn: false,
+        }
+    }
+
+    pub fn draw_frame(&mut self, highlight : & SolveHighlight) {
+        let mut stdout = stdout();
+        if self.drawn {
+            queue!(stdout, cursor::MoveUp(self.board.display_height()), cursor::MoveToColumn(0)).ok();
+        }
+        self.board.display_highlighted_in_context(&DisplayContext::new(false), highlight);
+        stdout.flush().ok();
+        self.drawn = true;
+        if !self.delay.is_zero() {
+            thread::sleep(self.delay);
+        }
+    }
+
+    //The search leaves its branches before returning, so the last frame is drawn from the solver's image
+    pub fn draw_result(&mut self, image : & ImageBoard) {
+        self.branches.clear();
+        self.board.image = image.clone();
+        self.draw_frame(&SolveHighlight::default());
+    }
+
+    pub fn get_image(&self) -> &ImageBoard {
+        &self.board.image
+    }
+}
+
+impl SolveObserver for SolveAnimation {
+    fn line_solved(&mut self, orientation : Orientation, idx : usize, before : & [CaseState], after : & [CaseState]) {
+        let mut highlight = SolveHighlight { line: Some((orientation, idx)), decided: Vec::new() };
+        for (i, value) in after.iter().enumerate() {
+            if before[i] != *value {
+                let (x, y) = match orientation {
+                    Orientation::Horizontal => (i, idx),
+                    Orientation::Vertical => (idx, i),
+                };
+                self.board.image.set_value(x, y, *value);
+                highlight.decided.push((x, y));
+            }
+        }
+        self.draw_frame(&highlight);
+    }
+
+    fn contradiction(&mut self, contradiction : Contradiction) {
+        let Contradiction::Line(orientation, idx) = contradiction;
+        self.draw_frame(&SolveHighlight { line: Some((orientation, idx)), decided: Vec::new() });
+    }
+
+    fn branch_pushed(&mut self, x : usize, y : usize, value : CaseState, _depth : usize) {
+        self.branches.push(self.board.image.clone());
+        self.board.image.set_value(x, y, value);
+        self.draw_frame(&SolveHighlight { line: None, decided: vec![(x, y)] });
+    }
+
+    fn branch_popped(&mut self, _depth : usize) {
+        if let Some(image) = self.branches.pop() {
+            self.board.image = image;
+        }
+    }
+}
+
+//Solves the picross like solve_picross_board, drawing every step
+pub fn animate_picross_solve(picross : &mut PicrossBoard, method : LineSolveMethod, delay : Duration) -> Solve {
+    let row_col_picross = RowColPicross::new(picross);
+    let animation = Arc::new(Mutex::new(SolveAnimation::new(&row_col_picross, delay)));
+    animate_row_col_picross_solve(picross, row_col_picross, &animation, method)
+}
+
+//Same as solve_row_col_picross, the animation ends on the image copied back in the board
+pub fn animate_row_col_picross_solve(picross : &mut PicrossBoard, mut row_col_picross : RowColPicross, animation : & Arc<Mutex<SolveAnimation>>, method : LineSolveMethod) -> Solve {
+    animation.lock().unwrap().draw_frame(&SolveHighlight::default());
+    row_col_picross.observer = Some(animation.clone());
+    let solve = solve_row_col_picross(picross, row_col_picross, method);
+    if let Solve::Partial | Solve::Full | Solve::Exhausted = solve {
+        animation.lock().unwrap().draw_result(&picross.image);
+    }
+    solve
+}
@@ -2,5 +2,8 @@ use super::*;
 
 pub mod image_board;
 pub mod image_line;
+pub mod packed_line;
+pub mod packed_board;
+pub mod netpbm_reader;
 
-pub use crate::image::{image_board::*, image_line::*};
\ No newline at end of file
+pub use crate::image::{image_board::*, image_line::*, packed_line::*, packed_board::*, netpbm_reader::*};
\ No newline at end of file
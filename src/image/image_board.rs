@@ -2,19 +2,42 @@ use super::*;
 
 #[repr(u8)]
 #[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "lowercase"))]
 pub enum CaseState {
     UNKNOWN = 0,
     ON = 1,
     OFF = 2,
 }
 
-#[derive(Debug)]
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(try_from = "ImageBoardFields"))]
 pub struct ImageBoard {
     height: usize,
     width: usize,
     values: Vec<CaseState>,
 }
 
+//Same fields as ImageBoard, only accepted when the values fill the board
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct ImageBoardFields {
+    height: usize,
+    width: usize,
+    values: Vec<CaseState>,
+}
+
+#[cfg(feature = "serde")]
+impl std::convert::TryFrom<ImageBoardFields> for ImageBoard {
+    type Error = String;
+
+    fn try_from(fields : ImageBoardFields) -> Result<Self, Self::Error> {
+        if fields.height.checked_mul(fields.width) != Some(fields.values.len()) {
+            return Err(format!("{} values for a {}x{} image", fields.values.len(), fields.width, fields.height));
+        }
+        Ok(Self { height: fields.height, width: fields.width, values: fields.values })
+    }
+}
+
 impl ImageBoard {
     pub fn new_empty(height: usize, width: usize) -> Self {
         Self {
@@ -35,16 +58,16 @@ impl ImageBoard {
         self.values[x + y * self.width]
     }
 
-    pub fn get_row(& self, idx : usize) -> ImageLine {
-        return ImageLine {
+    pub fn get_row(& self, idx : usize) -> ImageLine<'_> {
+        ImageLine {
             image : self,
             idx,
             orientation : Orientation::Horizontal,
         }
     }
 
-    pub fn get_col(& self, idx : usize) -> ImageLine {
-        return ImageLine {
+    pub fn get_col(& self, idx : usize) -> ImageLine<'_> {
+        ImageLine {
             image : self,
             idx,
             orientation : Orientation::Vertical,
@@ -55,8 +78,8 @@ impl ImageBoard {
 #[allow(dead_code)]
 pub fn create_one_line_board(values : & [CaseState]) -> ImageBoard {
     let mut image : ImageBoard = ImageBoard::new_empty(1, values.len());
-    for i in 0..values.len() {
-        image.set_value(i, 0, values[i]);
+    for (i, value) in values.iter().enumerate() {
+        image.set_value(i, 0, *value);
     }
     image
 }
@@ -77,6 +100,24 @@ pub fn create_cross_image_board(size: usize) -> ImageBoard {
     image
 }
 
+//Deterministic for a given seed, so generated picross can be used in tests
+#[allow(dead_code)]
+pub fn create_random_image_board(height: usize, width: usize, seed: u64) -> ImageBoard {
+    let mut image = ImageBoard::new_empty(height, width);
+    let mut state = seed;
+    for y in 0..height {
+        for x in 0..width {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            if (state >> 33) & 1 == 1 {
+                image.set_value(x, y, CaseState::ON);
+            } else {
+                image.set_value(x, y, CaseState::OFF);
+            }
+        }
+    }
+    image
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
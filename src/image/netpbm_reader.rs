@@ -0,0 +1,331 @@
+use super::*;
+
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum NetpbmError {
+    InvalidMagic,
+    //Byte offset of the value that is not a number or above the maximum value
+    InvalidValue(usize),
+    UnexpectedEnd,
+    //An image without pixels can not be scaled to a board size
+    EmptyImage,
+    Io(String),
+}
+
+impl fmt::Display for NetpbmError {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NetpbmError::InvalidMagic => write!(f, "not a netpbm image, P1 to P6 expected"),
+            NetpbmError::InvalidValue(pos) => write!(f, "invalid value at {}", pos),
+            NetpbmError::UnexpectedEnd => write!(f, "image ends before all pixels are read"),
+            NetpbmError::EmptyImage => write!(f, "image has no pixel to scale"),
+            NetpbmError::Io(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl Error for NetpbmError {}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct NetpbmOptions {
+    //Pixels darker than this luminance, between 0 and 1, are ON
+    pub threshold: f64,
+    //Height then width of the board, each cell takes the mean luminance of the pixels it covers
+    pub target_size: Option<(usize, usize)>,
+}
+
+impl Default for NetpbmOptions {
+    fn default() -> Self {
+        Self {
+            threshold: 0.5,
+            target_size: None,
+        }
+    }
+}
+
+struct NetpbmReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> NetpbmReader<'a> {
+    //Whitespace and comments, which run to the end of the line
+    fn skip_separators(&mut self) {
+        while self.pos < self.bytes.len() {
+            match self.bytes[self.pos] {
+                b'#' => {
+                    while self.pos < self.bytes.len() && self.bytes[self.pos] != b'\n' {
+                        self.pos += 1;
+                    }
+                }
+                c if c.is_ascii_whitespace() => self.pos += 1,
+                _ => break,
+            }
+        }
+    }
+
+    fn read_number(&mut self) -> Result<usize, NetpbmError> {
+        self.skip_separators();
+        let start = self.pos;
+        while self.pos < self.bytes.len() && self.bytes[self.pos].is_ascii_digit() {
+            self.pos += 1;
+        }
+        if start == self.pos {
+            return match self.pos {
+                pos if pos == self.bytes.len() => Err(NetpbmError::UnexpectedEnd),
+                pos => Err(NetpbmError::InvalidValue(pos)),
+            };
+        }
+        std::str::from_utf8(&self.bytes[start..self.pos]).unwrap().parse().map_err(|_| NetpbmError::InvalidValue(start))
+    }
+
+    fn read_sample(&mut self, max_value : usize) -> Result<f64, NetpbmError> {
+        let start = self.pos;
+        let value = self.read_number()?;
+        if value > max_value {
+            return Err(NetpbmError::InvalidValue(start));
+        }
+        Ok(value as f64 / max_value as f64)
+    }
+
+    //Plain bitmaps may have no space between pixels
+    fn read_bit(&mut self) -> Result<f64, NetpbmError> {
+        self.skip_separators();
+        match self.bytes.get(self.pos) {
+            Some(b'0') => { self.pos += 1; Ok(1.0) }
+            Some(b'1') => { self.pos += 1; Ok(0.0) }
+            Some(_) => Err(NetpbmError::InvalidValue(self.pos)),
+            None => Err(NetpbmError::UnexpectedEnd),
+        }
+    }
+
+    fn read_raw_sample(&mut self, max_value : usize) -> Result<f64, NetpbmError> {
+        let size = if max_value < 256 { 1 } else { 2 };
+        let sample = self.bytes.get(self.pos..self.pos + size).ok_or(NetpbmError::UnexpectedEnd)?;
+        let value = sample.iter().fold(0, |value, &byte| (value << 8) | byte as usize);
+        if value > max_value {
+            return Err(NetpbmError::InvalidValue(self.pos));
+        }
+        self.pos += size;
+        Ok(value as f64 / max_value as f64)
+    }
+}
+
+fn get_luminance(red : f64, green : f64, blue : f64) -> f64 {
+    0.299 * red + 0.587 * green + 0.114 * blue
+}
+
+//Luminance of every pixel, row by row, 0 is black and 1 is white
+fn read_netpbm_luminance(bytes : & [u8]) -> Result<(usize, usize, Vec<f64>), NetpbmError> {
+    if bytes.len() < 2 || bytes[0] != b'P' {
+        return Err(NetpbmError::InvalidMagic);
+    }
+    let format = bytes[1];
+    if !(b'1'..=b'6').contains(&format) {
+        return Err(NetpbmError::InvalidMagic);
+    }
+    let mut reader = NetpbmReader { bytes, pos: 2 };
+    reader.skip_separators();
+    let size_pos = reader.pos;
+    let width = reader.read_number()?;
+    let height = reader.read_number()?;
+    let max_value = match format {
+        b'1' | b'4' => 1,
+        _ => reader.read_number()?,
+    };
+    if max_value == 0 || max_value > 65535 {
+        return Err(NetpbmError::InvalidValue(reader.pos));
+    }
+    //A single whitespace separates the header from raw data
+    if format >= b'4' {
+        if reader.pos >= bytes.len() {
+            return Err(NetpbmError::UnexpectedEnd);
+        }
+        reader.pos += 1;
+    }
+
+    //The header is only trusted once the data left can hold every pixel
+    let pixel_count = width.checked_mul(height).ok_or(NetpbmError::InvalidValue(size_pos))?;
+    let sample_count = match format {
+        b'3' | b'6' => pixel_count.checked_mul(3),
+        _ => Some(pixel_count),
+    };
+    let data_size = match format {
+        b'4' => width.div_ceil(8).checked_mul(height),
+        b'5' | b'6' if max_value >= 256 => sample_count.and_then(|count| count.checked_mul(2)),
+        //Plain samples take at least one byte each
+        _ => sample_count,
+    };
+    match data_size {
+        Some(size) if size <= bytes.len().saturating_sub(reader.pos) => {},
+        _ => return Err(NetpbmError::UnexpectedEnd),
+    }
+
+    let mut luminance = Vec::with_capacity(pixel_count);
+    match format {
+        b'1' => {
+            for _ in 0..pixel_count {
+                luminance.push(reader.read_bit()?);
+            }
+        }
+        b'2' => {
+            for _ in 0..pixel_count {
+                luminance.push(reader.read_sample(max_value)?);
+            }
+        }
+        b'3' => {
+            for _ in 0..pixel_count {
+                let (red, green, blue) = (reader.read_sample(max_value)?, reader.read_sample(max_value)?, reader.read_sample(max_value)?);
+                luminance.push(get_luminance(red, green, blue));
+            }
+        }
+        //Rows of bits padded to a whole byte, the first pixel on the highest bit
+        b'4' => {
+            let row_size = width.div_ceil(8);
+            let data_end = row_size.checked_mul(height).and_then(|size| size.checked_add(reader.pos)).ok_or(NetpbmError::UnexpectedEnd)?;
+            let data = bytes.get(reader.pos..data_end).ok_or(NetpbmError::UnexpectedEnd)?;
+            for y in 0..height {
+                for x in 0..width {
+                    let bit = (data[y * row_size + x / 8] >> (7 - x % 8)) & 1;
+                    luminance.push(if bit == 1 { 0.0 } else { 1.0 });
+                }
+            }
+        }
+        b'5' => {
+            for _ in 0..pixel_count {
+                luminance.push(reader.read_raw_sample(max_value)?);
+            }
+        }
+        _ => {
+            for _ in 0..pixel_count {
+                let (red, green, blue) = (reader.read_raw_sample(max_value)?, reader.read_raw_sample(max_value)?, reader.read_raw_sample(max_value)?);
+                luminance.push(get_luminance(red, green, blue));
+            }
+        }
+    }
+    Ok((height, width, luminance))
+}
+
+//Pixels covered by the cell idx when size pixels are spread over count cells, at least one
+fn get_cell_range(idx : usize, count : usize, size : usize) -> std::ops::Range<usize> {
+    let start = idx * size / count;
+    let end = std::cmp::max((idx + 1) * size / count, start + 1);
+    start..std::cmp::min(end, size)
+}
+
+pub fn image_from_netpbm_bytes(bytes : & [u8], options : & NetpbmOptions) -> Result<ImageBoard, NetpbmError> {
+    let (height, width, luminance) = read_netpbm_luminance(bytes)?;
+    let (target_height, target_width) = options.target_size.unwrap_or((height, width));
+    let mut image = ImageBoard::new_empty(target_height, target_width);
+    if height == 0 || width == 0 {
+        return match options.target_size {
+            Some(_) if target_height > 0 && target_width > 0 => Err(NetpbmError::EmptyImage),
+            _ => Ok(image),
+        };
+    }
+    for y in 0..target_height {
+        for x in 0..target_width {
+            let rows = get_cell_range(y, target_height, height);
+            let cols = get_cell_range(x, target_width, width);
+            let count = rows.len() * cols.len();
+            let sum : f64 = rows.flat_map(|py| cols.clone().map(move |px| (px, py)))
+                .map(|(px, py)| luminance[py * width + px])
+                .sum();
+            let value = if sum / (count as f64) < options.threshold { CaseState::ON } else { CaseState::OFF };
+            image.set_value(x, y, value);
+        }
+    }
+    Ok(image)
+}
+
+pub fn read_netpbm_file<P : AsRef<Path>>(path : P, options : & NetpbmOptions) -> Result<ImageBoard, NetpbmError> {
+    let bytes = fs::read(path).map_err(|e| NetpbmError::Io(e.to_string()))?;
+    image_from_netpbm_bytes(&bytes, options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::image::CaseState::*;
+
+    fn get_row_values(image : & ImageBoard, y : usize) -> Vec<CaseState> {
+        (0..image.width()).map(|x| image.get_value(x, y)).collect()
+    }
+
+    #[test]
+    fn test_netpbm_plain_formats() {
+        let options = NetpbmOptions::default();
+        let image = image_from_netpbm_bytes(b"P1\n# comment\n3 2\n010\n1 1 0\n", &options).unwrap();
+        assert_eq!(image.height(), 2);
+        assert_eq!(image.width(), 3);
+        assert_eq!(get_row_values(&image, 0), vec![OFF, ON, OFF]);
+        assert_eq!(get_row_values(&image, 1), vec![ON, ON, OFF]);
+
+        let image = image_from_netpbm_bytes(b"P2 2 1 255 10 200", &options).unwrap();
+        assert_eq!(get_row_values(&image, 0), vec![ON, OFF]);
+
+        let image = image_from_netpbm_bytes(b"P3 2 1 15\n15 15 15  15 0 15", &options).unwrap();
+        assert_eq!(get_row_values(&image, 0), vec![OFF, ON]);
+    }
+
+    #[test]
+    fn test_netpbm_raw_formats() {
+        let options = NetpbmOptions::default();
+        let image = image_from_netpbm_bytes(b"P4\n10 2\n\x81\x40\x00\xC0", &options).unwrap();
+        assert_eq!(get_row_values(&image, 0), vec![ON, OFF, OFF, OFF, OFF, OFF, OFF, ON, OFF, ON]);
+        assert_eq!(get_row_values(&image, 1), vec![OFF, OFF, OFF, OFF, OFF, OFF, OFF, OFF, ON, ON]);
+
+        let image = image_from_netpbm_bytes(b"P5 3 1 255\n\x00\x80\xff", &options).unwrap();
+        assert_eq!(get_row_values(&image, 0), vec![ON, OFF, OFF]);
+
+        let image = image_from_netpbm_bytes(b"P5 2 1 65535\n\x10\x00\xff\xff", &options).unwrap();
+        assert_eq!(get_row_values(&image, 0), vec![ON, OFF]);
+
+        let image = image_from_netpbm_bytes(b"P6 2 1 255\n\xff\xff\xff\x00\x00\xff", &options).unwrap();
+        assert_eq!(get_row_values(&image, 0), vec![OFF, ON]);
+    }
+
+    #[test]
+    fn test_netpbm_threshold_and_downscale() {
+        let gray = b"P2 4 2 100 10 30 60 90 10 30 60 90";
+        let image = image_from_netpbm_bytes(gray, &NetpbmOptions { threshold: 0.7, target_size: None }).unwrap();
+        assert_eq!(get_row_values(&image, 0), vec![ON, ON, ON, OFF]);
+
+        let options = NetpbmOptions { threshold: 0.5, target_size: Some((1, 2)) };
+        let image = image_from_netpbm_bytes(gray, &options).unwrap();
+        assert_eq!(image.height(), 1);
+        assert_eq!(get_row_values(&image, 0), vec![ON, OFF]);
+
+        let options = NetpbmOptions { threshold: 0.5, target_size: Some((2, 8)) };
+        let image = image_from_netpbm_bytes(gray, &options).unwrap();
+        assert_eq!(get_row_values(&image, 1), vec![ON, ON, ON, ON, OFF, OFF, OFF, OFF]);
+    }
+
+    #[test]
+    fn test_netpbm_errors() {
+        let options = NetpbmOptions::default();
+        assert_eq!(image_from_netpbm_bytes(b"P7 1 1", &options), Err(NetpbmError::InvalidMagic));
+        assert_eq!(image_from_netpbm_bytes(b"P1 2 2 0 1 1", &options), Err(NetpbmError::UnexpectedEnd));
+        assert_eq!(image_from_netpbm_bytes(b"P1 1 1 2", &options), Err(NetpbmError::InvalidValue(7)));
+        assert_eq!(image_from_netpbm_bytes(b"P2 1 1 10 11", &options), Err(NetpbmError::InvalidValue(9)));
+        assert_eq!(image_from_netpbm_bytes(b"P5 2 1 255\n\x00", &options), Err(NetpbmError::UnexpectedEnd));
+        assert_eq!(image_from_netpbm_bytes(b"P5 4000000000 4000000000 255\n\x00", &options), Err(NetpbmError::UnexpectedEnd));
+        assert_eq!(image_from_netpbm_bytes(b"P4 4000000000 4000000000\n\x00", &options), Err(NetpbmError::UnexpectedEnd));
+        assert_eq!(image_from_netpbm_bytes(b"P2 99999999999 99999999999 255 0", &options), Err(NetpbmError::InvalidValue(3)));
+        assert_eq!(image_from_netpbm_bytes(b"P6 1 1 65535\n\x00\x00\x00\x00\x00", &options), Err(NetpbmError::UnexpectedEnd));
+        assert_eq!(image_from_netpbm_bytes(b"P4 8 1", &options), Err(NetpbmError::UnexpectedEnd));
+        assert_eq!(image_from_netpbm_bytes(b"P4 0 0", &options), Err(NetpbmError::UnexpectedEnd));
+        assert_eq!(image_from_netpbm_bytes(b"P5 0 0 255", &options), Err(NetpbmError::UnexpectedEnd));
+        assert_eq!(image_from_netpbm_bytes(b"P5 2 2 255", &options), Err(NetpbmError::UnexpectedEnd));
+        assert_eq!(image_from_netpbm_bytes(b"P2 0 0 255", &options).map(|image| image.width()), Ok(0));
+        let scaled = NetpbmOptions { threshold: 0.5, target_size: Some((2, 2)) };
+        assert_eq!(image_from_netpbm_bytes(b"P2 0 0 255", &scaled), Err(NetpbmError::EmptyImage));
+        assert_eq!(image_from_netpbm_bytes(b"P4 0 3\n", &scaled), Err(NetpbmError::EmptyImage));
+        assert!(matches!(read_netpbm_file("missing.pbm", &options), Err(NetpbmError::Io(_))));
+    }
+}
@@ -0,0 +1,69 @@
+use super::*;
+
+//Rows of an image packed once, so every line of the board can be checked word by word
+#[derive(Clone, PartialEq, Debug)]
+pub struct PackedBoard {
+    height: usize,
+    width: usize,
+    rows: Vec<PackedLine>,
+}
+
+impl PackedBoard {
+    pub fn from_image(image : & ImageBoard) -> Self {
+        Self {
+            height: image.height(),
+            width: image.width(),
+            rows: (0..image.height()).map(|y| PackedLine::from_image_line(&image.get_row(y))).collect(),
+        }
+    }
+
+    pub fn width(&self) -> usize { self.width }
+    pub fn height(&self) -> usize { self.height }
+
+    pub fn get_value(&self, x : usize, y : usize) -> CaseState {
+        self.rows[y].get(x)
+    }
+
+    pub fn get_row(&self, idx : usize) -> &PackedLine {
+        &self.rows[idx]
+    }
+
+    //Columns are stored across the rows, so they are gathered cell by cell
+    pub fn get_col(&self, idx : usize) -> PackedLine {
+        let mut line = PackedLine::new(self.height);
+        for (y, row) in self.rows.iter().enumerate() {
+            line.set(y, row.get(idx));
+        }
+        line
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_packed_board_from_image() {
+        let image = create_cross_image_board(7);
+        let board = PackedBoard::from_image(&image);
+        assert_eq!(board.height(), 7);
+        assert_eq!(board.width(), 7);
+
+        let col = board.get_col(3);
+        for y in 0..7 {
+            assert!(col.get(y) == image.get_value(3, y));
+            assert!(board.get_row(y).get(2) == image.get_value(2, y));
+            assert!(board.get_value(5, y) == image.get_value(5, y));
+        }
+    }
+
+    #[test]
+    fn test_packed_board_unknown_cells() {
+        let mut image = ImageBoard::new_empty(2, 3);
+        image.set_value(1, 0, CaseState::ON);
+        image.set_value(1, 1, CaseState::OFF);
+        let board = PackedBoard::from_image(&image);
+        assert_eq!(board.get_col(1).to_values(), vec![CaseState::ON, CaseState::OFF]);
+        assert_eq!(board.get_row(0).unknown_count(), 2);
+    }
+}
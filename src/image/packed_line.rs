@@ -0,0 +1,215 @@
+use super::*;
+
+pub const WORD_SIZE : usize = 64;
+
+//Two bits per cell spread over two masks : known ON and known OFF, neither set means UNKNOWN
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct PackedLine {
+    size: usize,
+    on: Vec<u64>,
+    off: Vec<u64>,
+}
+
+fn word_count(size : usize) -> usize {
+    size.div_ceil(WORD_SIZE)
+}
+
+//Mask of the bits from start to end (excluded) inside a single word
+fn range_mask(start : usize, end : usize) -> u64 {
+    let high = if end == WORD_SIZE { u64::MAX } else { (1u64 << end) - 1 };
+    high & !((1u64 << start) - 1)
+}
+
+impl PackedLine {
+    pub fn new(size : usize) -> Self {
+        Self {
+            size,
+            on: vec![0; word_count(size)],
+            off: vec![0; word_count(size)],
+        }
+    }
+
+    pub fn from_values(values : & [CaseState]) -> Self {
+        let mut line = Self::new(values.len());
+        for (i, value) in values.iter().enumerate() {
+            line.set(i, *value);
+        }
+        line
+    }
+
+    pub fn from_image_line(image_line : & ImageLine) -> Self {
+        let mut line = Self::new(image_line.size());
+        for i in 0..image_line.size() {
+            line.set(i, image_line.get(i));
+        }
+        line
+    }
+
+    pub fn to_values(&self) -> Vec<CaseState> {
+        (0..self.size).map(|i| self.get(i)).collect()
+    }
+
+    pub fn size(&self) -> usize { self.size }
+
+    pub fn get(&self, idx : usize) -> CaseState {
+        let bit = 1u64 << (idx % WORD_SIZE);
+        if self.on[idx / WORD_SIZE] & bit != 0 {
+            CaseState::ON
+        } else if self.off[idx / WORD_SIZE] & bit != 0 {
+            CaseState::OFF
+        } else {
+            CaseState::UNKNOWN
+        }
+    }
+
+    pub fn set(&mut self, idx : usize, val : CaseState) {
+        let word = idx / WORD_SIZE;
+        let bit = 1u64 << (idx % WORD_SIZE);
+        self.on[word] &= !bit;
+        self.off[word] &= !bit;
+        match val {
+            CaseState::ON => self.on[word] |= bit,
+            CaseState::OFF => self.off[word] |= bit,
+            CaseState::UNKNOWN => {},
+        }
+    }
+
+    fn for_each_word_in_range<F : FnMut(usize, u64)>(start : usize, end : usize, mut f : F) {
+        let mut idx = start;
+        while idx < end {
+            let word = idx / WORD_SIZE;
+            let word_end = std::cmp::min(end, (word + 1) * WORD_SIZE);
+            f(word, range_mask(idx % WORD_SIZE, word_end - word * WORD_SIZE));
+            idx = word_end;
+        }
+    }
+
+    pub fn fill(&mut self, start : usize, end : usize, val : CaseState) {
+        let (on, off) = (&mut self.on, &mut self.off);
+        Self::for_each_word_in_range(start, end, |word, mask| {
+            on[word] &= !mask;
+            off[word] &= !mask;
+            match val {
+                CaseState::ON => on[word] |= mask,
+                CaseState::OFF => off[word] |= mask,
+                CaseState::UNKNOWN => {},
+            }
+        });
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.unknown_count() == 0
+    }
+
+    pub fn unknown_count(&self) -> usize {
+        let known : u32 = self.on.iter().zip(self.off.iter()).map(|(on, off)| (on | off).count_ones()).sum();
+        self.size - known as usize
+    }
+
+    //No cell is ON in one line and OFF in the other
+    pub fn is_compatible(&self, other : & PackedLine) -> bool {
+        self.on.iter().zip(other.off.iter()).all(|(on, off)| on & off == 0)
+            && self.off.iter().zip(other.on.iter()).all(|(off, on)| off & on == 0)
+    }
+
+    //Only the cells both lines agree on stay known
+    pub fn intersect(&mut self, other : & PackedLine) {
+        for (word, other_word) in self.on.iter_mut().zip(other.on.iter()) {
+            *word &= other_word;
+        }
+        for (word, other_word) in self.off.iter_mut().zip(other.off.iter()) {
+            *word &= other_word;
+        }
+    }
+
+    //Sizes of the runs of known ON cells, found word by word
+    pub fn get_on_runs(&self) -> Vec<usize> {
+        let mut runs = Vec::new();
+        let mut current = 0;
+        for (word_idx, &word) in self.on.iter().enumerate() {
+            let bits = std::cmp::min(WORD_SIZE, self.size - word_idx * WORD_SIZE);
+            let mut remaining = word;
+            let mut consumed = 0;
+            while consumed < bits {
+                if remaining & 1 == 1 {
+                    let ones = std::cmp::min(remaining.trailing_ones() as usize, bits - consumed);
+                    current += ones;
+                    consumed += ones;
+                    remaining = remaining.checked_shr(ones as u32).unwrap_or(0);
+                } else {
+                    if current > 0 {
+                        runs.push(current);
+                        current = 0;
+                    }
+                    let zeros = std::cmp::min(remaining.trailing_zeros() as usize, bits - consumed);
+                    consumed += zeros;
+                    remaining = remaining.checked_shr(zeros as u32).unwrap_or(0);
+                }
+            }
+        }
+        if current > 0 {
+            runs.push(current);
+        }
+        runs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::image::CaseState::*;
+
+    #[test]
+    fn test_packed_line_access() {
+        let values = vec![ON, OFF, UNKNOWN, ON];
+        let mut line = PackedLine::from_values(&values);
+        assert_eq!(line.size(), 4);
+        assert_eq!(line.to_values(), values);
+        assert_eq!(line.unknown_count(), 1);
+
+        line.set(0, UNKNOWN);
+        line.set(2, OFF);
+        assert_eq!(line.to_values(), vec![UNKNOWN, OFF, OFF, ON]);
+    }
+
+    #[test]
+    fn test_packed_line_ranges() {
+        let mut line = PackedLine::new(150);
+        line.fill(60, 130, ON);
+        assert!(line.get(59) == UNKNOWN);
+        assert!(line.get(60) == ON);
+        assert!(line.get(129) == ON);
+        assert!(line.get(130) == UNKNOWN);
+        assert_eq!(line.unknown_count(), 80);
+
+        line.fill(64, 128, OFF);
+        assert!(line.get(127) == OFF);
+        assert_eq!(line.get_on_runs(), vec![4, 2]);
+    }
+
+    #[test]
+    fn test_packed_line_runs() {
+        let line = PackedLine::from_values(&[ON, ON, OFF, UNKNOWN, ON, OFF, ON, ON, ON]);
+        assert_eq!(line.get_on_runs(), vec![2, 1, 3]);
+
+        let mut line = PackedLine::new(200);
+        line.fill(0, 200, ON);
+        assert_eq!(line.get_on_runs(), vec![200]);
+        assert!(PackedLine::new(0).get_on_runs().is_empty());
+    }
+
+    #[test]
+    fn test_packed_line_combination() {
+        let first = PackedLine::from_values(&[ON, OFF, ON, UNKNOWN]);
+        let second = PackedLine::from_values(&[ON, ON, ON, OFF]);
+        assert!(!first.is_compatible(&second));
+
+        let mut common = first.clone();
+        common.intersect(&second);
+        assert_eq!(common.to_values(), vec![ON, UNKNOWN, ON, UNKNOWN]);
+
+        let third = PackedLine::from_values(&[UNKNOWN, OFF, UNKNOWN, OFF]);
+        assert!(first.is_compatible(&third));
+    }
+}
@@ -1,9 +1,11 @@
 use picross_solver::display::*;
+use picross_solver::solver::*;
 
 extern crate getopts;
 
 use getopts::Options;
 use std::env;
+use std::time::Duration;
 
 
 fn print_usage(program: &str, opts: Options) {
@@ -18,6 +20,11 @@ fn main() {
     let mut opts = Options::new();
     opts.optopt("c", "cross_test", "Draw a cross example", "SIZE");
     opts.optopt("d", "draw_picross", "Draw a picross example", "SIZE");
+    opts.optopt("a", "animate", "Solve a picross given by its clues, drawing every line solved", "CLUES");
+    opts.optopt("t", "delay", "Delay between two frames of the animation", "MILLISECONDS");
+    opts.optopt("i", "image", "Create a picross from a PBM, PGM or PPM image and print its clues", "FILE");
+    opts.optopt("l", "threshold", "Luminance between 0 and 1 under which a pixel of the image is ON", "THRESHOLD");
+    opts.optopt("s", "size", "Scale the image to this size before creating the picross", "WIDTHxHEIGHT");
     opts.optflag("h", "help", "Print this help menu");
     let matches = match opts.parse(&args[1..]) {
         Ok(m) => { m }
@@ -45,4 +52,38 @@ fn main() {
         let picross = PicrossBoard::new_empty(size, size);
         picross.display();
     }
+    if matches.opt_present("a") {
+        let clues = matches.opt_str("a").unwrap();
+        let delay = match matches.opt_str("t") {
+            Some(x) => x.parse().unwrap(),
+            None => 50,
+        };
+        let mut picross = match PicrossBoard::picross_from_clue_string(&clues) {
+            Ok(picross) => picross,
+            Err(e) => { panic!("Invalid clues {:?} : {}", clues, e) }
+        };
+        let solve = animate_picross_solve(&mut picross, LineSolveMethod::Dynamic, Duration::from_millis(delay));
+        println!("{:?}", solve);
+    }
+    if matches.opt_present("i") {
+        let path = matches.opt_str("i").unwrap();
+        let mut options = NetpbmOptions::default();
+        if let Some(x) = matches.opt_str("l") {
+            options.threshold = x.parse().unwrap();
+        }
+        if let Some(x) = matches.opt_str("s") {
+            let size : Vec<usize> = x.split('x').map(|value| value.parse().unwrap()).collect();
+            if size.len() != 2 {
+                panic!("Invalid size {:?}, WIDTHxHEIGHT expected", x);
+            }
+            options.target_size = Some((size[1], size[0]));
+        }
+        let image = match read_netpbm_file(&path, &options) {
+            Ok(image) => image,
+            Err(e) => { panic!("Invalid image {:?} : {}", path, e) }
+        };
+        let picross = PicrossBoard::new_from_image(&image);
+        println!("{}", picross.to_clue_string());
+        picross.display();
+    }
 }
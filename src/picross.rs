@@ -6,4 +6,6 @@ pub use clues::*;
 pub mod picross_board;
 pub mod picross_line_unit;
 pub mod picross_samples;
-pub use crate::picross::{picross_board::*, picross_line_unit::*, picross_samples::*};
\ No newline at end of file
+pub mod non_format;
+pub mod pbn_format;
+pub use crate::picross::{picross_board::*, picross_line_unit::*, picross_samples::*, pbn_format::*};
\ No newline at end of file
@@ -0,0 +1,262 @@
+use super::*;
+
+use std::error::Error;
+use std::fmt;
+
+//Line numbers start at 1
+#[derive(Clone, PartialEq, Debug)]
+pub enum NonParseError {
+    InvalidNumber(usize, String),
+    InvalidGoal(usize),
+    //Clue line outside of the rows and columns sections
+    UnexpectedLine(usize),
+    MissingRows,
+    MissingColumns,
+    //Declared size, then the count of clue lines found
+    RowCountMismatch(usize, usize),
+    ColumnCountMismatch(usize, usize),
+}
+
+impl fmt::Display for NonParseError {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NonParseError::InvalidNumber(line, value) => write!(f, "invalid number \"{}\" at line {}", value, line),
+            NonParseError::InvalidGoal(line) => write!(f, "goal at line {} does not match the size of the puzzle", line),
+            NonParseError::UnexpectedLine(line) => write!(f, "clues outside of the rows and columns sections at line {}", line),
+            NonParseError::MissingRows => write!(f, "no rows section"),
+            NonParseError::MissingColumns => write!(f, "no columns section"),
+            NonParseError::RowCountMismatch(height, count) => write!(f, "height is {} but {} rows are given", height, count),
+            NonParseError::ColumnCountMismatch(width, count) => write!(f, "width is {} but {} columns are given", width, count),
+        }
+    }
+}
+
+impl Error for NonParseError {}
+
+#[derive(PartialEq)]
+enum NonSection {
+    Header,
+    Rows,
+    Columns,
+}
+
+fn unquote(value : & str) -> String {
+    value.trim().trim_matches('"').to_string()
+}
+
+fn parse_number(value : & str, line_number : usize) -> Result<usize, NonParseError> {
+    value.trim().parse().map_err(|_| NonParseError::InvalidNumber(line_number, value.trim().to_string()))
+}
+
+fn parse_clue_line(line : & str, line_number : usize) -> Result<ClueLine, NonParseError> {
+    line.split(|c : char| c == ',' || c.is_whitespace())
+        .filter(|value| !value.is_empty())
+        .map(|value| parse_number(value, line_number))
+        .collect()
+}
+
+//Goal cells row by row, 1 for ON and 0 for OFF, the value may be quoted
+fn parse_goal(value : & str, height : usize, width : usize, line_number : usize) -> Result<ImageBoard, NonParseError> {
+    let cells : Vec<char> = unquote(value).chars().filter(|c| !c.is_whitespace()).collect();
+    if cells.len() != height * width {
+        return Err(NonParseError::InvalidGoal(line_number));
+    }
+    let mut image = ImageBoard::new_empty(height, width);
+    for (i, c) in cells.iter().enumerate() {
+        let value = match c {
+            '1' => CaseState::ON,
+            '0' => CaseState::OFF,
+            _ => return Err(NonParseError::InvalidGoal(line_number)),
+        };
+        image.set_value(i % width, i / width, value);
+    }
+    Ok(image)
+}
+
+//Clue lines start with a digit, every other line is a keyword with an optional value
+pub fn picross_from_non_string(text : & str) -> Result<PicrossBoard, NonParseError> {
+    let mut metadata = PicrossMetadata::default();
+    let mut width : Option<usize> = None;
+    let mut height : Option<usize> = None;
+    let mut rows : Option<ClueArray> = None;
+    let mut columns : Option<ClueArray> = None;
+    let mut goal : Option<(String, usize)> = None;
+    let mut section = NonSection::Header;
+
+    for (i, line) in text.lines().enumerate() {
+        let line_number = i + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        if line.starts_with(|c : char| c.is_ascii_digit()) {
+            let clues = parse_clue_line(line, line_number)?;
+            match section {
+                NonSection::Rows => rows.get_or_insert_with(Vec::new).push(clues),
+                NonSection::Columns => columns.get_or_insert_with(Vec::new).push(clues),
+                NonSection::Header => return Err(NonParseError::UnexpectedLine(line_number)),
+            }
+            continue;
+        }
+
+        let (key, value) = match line.find(char::is_whitespace) {
+            Some(pos) => (&line[..pos], line[pos..].trim()),
+            None => (line, ""),
+        };
+        section = NonSection::Header;
+        match key {
+            "width" => width = Some(parse_number(value, line_number)?),
+            "height" => height = Some(parse_number(value, line_number)?),
+            "rows" => {
+                section = NonSection::Rows;
+                rows.get_or_insert_with(Vec::new);
+            },
+            "columns" => {
+                section = NonSection::Columns;
+                columns.get_or_insert_with(Vec::new);
+            },
+            "goal" => goal = Some((value.to_string(), line_number)),
+            "title" => metadata.title = Some(unquote(value)),
+            "by" => metadata.author = Some(unquote(value)),
+            _ => metadata.extra.push((key.to_string(), value.to_string())),
+        }
+    }
+
+    let rows = rows.ok_or(NonParseError::MissingRows)?;
+    let columns = columns.ok_or(NonParseError::MissingColumns)?;
+    if let Some(height) = height {
+        if height != rows.len() {
+            return Err(NonParseError::RowCountMismatch(height, rows.len()));
+        }
+    }
+    if let Some(width) = width {
+        if width != columns.len() {
+            return Err(NonParseError::ColumnCountMismatch(width, columns.len()));
+        }
+    }
+
+    let mut picross = PicrossBoard::new_empty(rows.len(), columns.len());
+    if let Some((value, line_number)) = goal {
+        picross.image = parse_goal(&value, rows.len(), columns.len(), line_number)?;
+    }
+    picross.left = ClueBoard::new_from_clue_array(Orientation::Vertical, rows);
+    picross.top = ClueBoard::new_from_clue_array(Orientation::Horizontal, columns);
+    picross.metadata = metadata;
+    Ok(picross)
+}
+
+fn write_clue_section(text : &mut String, name : & str, values : & ClueArray) {
+    text.push_str(&format!("\n{}\n", name));
+    for line in values.iter() {
+        if line.is_empty() {
+            text.push_str("0\n");
+        } else {
+            let clues : Vec<String> = line.iter().map(|clue| clue.to_string()).collect();
+            text.push_str(&format!("{}\n", clues.join(",")));
+        }
+    }
+}
+
+//The goal is only written when every cell of the image is known
+pub fn picross_to_non_string(picross : & PicrossBoard) -> String {
+    let mut text = String::new();
+    if let Some(title) = &picross.metadata.title {
+        text.push_str(&format!("title \"{}\"\n", title));
+    }
+    if let Some(author) = &picross.metadata.author {
+        text.push_str(&format!("by \"{}\"\n", author));
+    }
+    for (key, value) in picross.metadata.extra.iter() {
+        if value.is_empty() {
+            text.push_str(&format!("{}\n", key));
+        } else {
+            text.push_str(&format!("{} {}\n", key, value));
+        }
+    }
+    text.push_str(&format!("width {}\nheight {}\n", picross.width(), picross.height()));
+    write_clue_section(&mut text, "rows", picross.left.get_clue_array());
+    write_clue_section(&mut text, "columns", picross.top.get_clue_array());
+
+    let image = &picross.image;
+    let cells : Option<String> = (0..image.height())
+        .flat_map(|y| (0..image.width()).map(move |x| (x, y)))
+        .map(|(x, y)| match image.get_value(x, y) {
+            CaseState::ON => Some('1'),
+            CaseState::OFF => Some('0'),
+            CaseState::UNKNOWN => None,
+        })
+        .collect();
+    if let Some(cells) = cells {
+        text.push_str(&format!("\ngoal \"{}\"\n", cells));
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NON_SAMPLE : &str = "catalogue \"sample 1\"
+title \"Cross\"
+by \"Someone\"
+copyright \"public domain\"
+width 3
+height 3
+
+rows
+1
+3
+1
+
+columns
+1
+3
+1
+
+goal \"010111010\"
+";
+
+    #[test]
+    fn test_non_read() {
+        let picross = picross_from_non_string(NON_SAMPLE).unwrap();
+        assert_eq!(picross.height(), 3);
+        assert_eq!(picross.width(), 3);
+        assert_eq!(picross.left.get_line_copy(1), vec![3]);
+        assert_eq!(picross.top.get_line_copy(0), vec![1]);
+        assert!(picross.image.get_value(1, 0) == CaseState::ON);
+        assert!(picross.image.get_value(0, 0) == CaseState::OFF);
+        assert_eq!(picross.metadata.title.as_deref(), Some("Cross"));
+        assert_eq!(picross.metadata.author.as_deref(), Some("Someone"));
+        assert_eq!(picross.metadata.extra.len(), 2);
+    }
+
+    #[test]
+    fn test_non_round_trip() {
+        let picross = picross_from_non_string(NON_SAMPLE).unwrap();
+        let text = picross_to_non_string(&picross);
+        let parsed = picross_from_non_string(&text).unwrap();
+        assert_eq!(parsed.metadata, picross.metadata);
+        assert!(parsed.image == picross.image);
+        assert_eq!(parsed.to_clue_string(), picross.to_clue_string());
+        assert_eq!(picross_to_non_string(&parsed), text);
+        assert_eq!(crate::clues::non_format::picross_to_non_string(&parsed), crate::clues::picross_to_non_string(&parsed));
+
+        //Without goal and with multi-digit and empty lines
+        let image = create_random_image_board(12, 14, 3);
+        let picross = PicrossBoard::new_from_image(&image);
+        let text = picross_to_non_string(&picross);
+        assert!(!text.contains("goal"));
+        let parsed = picross_from_non_string(&text).unwrap();
+        assert_eq!(parsed.to_clue_string(), picross.to_clue_string());
+    }
+
+    #[test]
+    fn test_non_errors() {
+        assert_eq!(picross_from_non_string("width 1\nrows\n1\n").err(), Some(NonParseError::MissingColumns));
+        assert_eq!(picross_from_non_string("1\nrows\n1\ncolumns\n1\n").err(), Some(NonParseError::UnexpectedLine(1)));
+        assert_eq!(picross_from_non_string("rows\n1,x\ncolumns\n1\n").err(), Some(NonParseError::InvalidNumber(2, "x".to_string())));
+        assert_eq!(picross_from_non_string("height 2\nrows\n1\ncolumns\n1\n").err(), Some(NonParseError::RowCountMismatch(2, 1)));
+        assert_eq!(picross_from_non_string("rows\n1\ncolumns\n1\ngoal 10\n").err(), Some(NonParseError::InvalidGoal(5)));
+    }
+}
@@ -0,0 +1,505 @@
+use super::*;
+
+use std::error::Error;
+use std::fmt;
+
+//Positions are byte offsets in the XML text
+#[derive(Clone, PartialEq, Debug)]
+pub enum PbnParseError {
+    InvalidXml(usize),
+    //Tag opened at the first position, closed by another name at the second
+    MismatchedTag(usize, usize),
+    MissingElement(String),
+    InvalidNumber(String),
+    //Only black and white puzzles are supported
+    UnsupportedColor(String),
+    InvalidSolution,
+}
+
+impl fmt::Display for PbnParseError {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PbnParseError::InvalidXml(pos) => write!(f, "invalid XML at {}", pos),
+            PbnParseError::MismatchedTag(open_pos, close_pos) => write!(f, "tag opened at {} is closed by another tag at {}", open_pos, close_pos),
+            PbnParseError::MissingElement(name) => write!(f, "no {} element", name),
+            PbnParseError::InvalidNumber(value) => write!(f, "invalid count \"{}\"", value),
+            PbnParseError::UnsupportedColor(color) => write!(f, "color {} is not supported, only two color puzzles are", color),
+            PbnParseError::InvalidSolution => write!(f, "solution image does not match the size of the puzzle"),
+        }
+    }
+}
+
+impl Error for PbnParseError {}
+
+//Just enough XML for the pbn files : elements, attributes, text, comments and declarations are skipped
+#[derive(Debug)]
+struct XmlElement {
+    name: String,
+    attributes: Vec<(String, String)>,
+    children: Vec<XmlElement>,
+    text: String,
+}
+
+impl XmlElement {
+    fn new(name : & str) -> Self {
+        Self {
+            name: name.to_string(),
+            attributes: Vec::new(),
+            children: Vec::new(),
+            text: String::new(),
+        }
+    }
+
+    fn get_attribute(&self, name : & str) -> Option<&str> {
+        self.attributes.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str())
+    }
+
+    fn get_child(&self, name : & str) -> Option<&XmlElement> {
+        self.children.iter().find(|child| child.name == name)
+    }
+
+    fn get_children<'a>(&'a self, name : &'a str) -> impl Iterator<Item = &'a XmlElement> {
+        self.children.iter().filter(move |child| child.name == name)
+    }
+
+    fn with_text(mut self, text : & str) -> Self {
+        self.text = text.to_string();
+        self
+    }
+
+    fn with_attribute(mut self, name : & str, value : & str) -> Self {
+        self.attributes.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    //Elements with only leaves as children are written on a single line, like <line><count>1</count></line>
+    fn write(&self, text : &mut String, indent : Option<usize>) {
+        if let Some(indent) = indent {
+            text.push_str(&"  ".repeat(indent));
+        }
+        text.push('<');
+        text.push_str(&self.name);
+        for (key, value) in self.attributes.iter() {
+            text.push_str(&format!(" {}=\"{}\"", key, escape_xml(value)));
+        }
+        if self.children.is_empty() && self.text.is_empty() {
+            text.push_str("/>");
+        } else {
+            text.push('>');
+            text.push_str(&escape_xml(&self.text));
+            let inline = indent.is_none() || self.children.iter().all(|child| child.children.is_empty());
+            if inline {
+                for child in self.children.iter() {
+                    child.write(text, None);
+                }
+            } else {
+                text.push('\n');
+                for child in self.children.iter() {
+                    child.write(text, indent.map(|indent| indent + 1));
+                }
+                text.push_str(&"  ".repeat(indent.unwrap_or(0)));
+            }
+            text.push_str(&format!("</{}>", self.name));
+        }
+        if indent.is_some() {
+            text.push('\n');
+        }
+    }
+}
+
+fn escape_xml(text : & str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+fn unescape_xml(text : & str) -> String {
+    let mut result = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let end = match rest.find(';') {
+            Some(end) => end,
+            None => break,
+        };
+        let entity = &rest[1..end];
+        let decoded = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "copy" => Some('©'),
+            _ if entity.starts_with("#x") => u32::from_str_radix(&entity[2..], 16).ok().and_then(char::from_u32),
+            _ if entity.starts_with('#') => entity[1..].parse().ok().and_then(char::from_u32),
+            _ => None,
+        };
+        match decoded {
+            Some(c) => result.push(c),
+            //Unknown entities are kept as they are
+            None => result.push_str(&rest[..=end]),
+        }
+        rest = &rest[end + 1..];
+    }
+    result.push_str(rest);
+    result
+}
+
+struct XmlReader<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> XmlReader<'a> {
+    fn rest(&self) -> &'a str {
+        &self.text[self.pos..]
+    }
+
+    fn skip_until(&mut self, end : & str) -> Result<(), PbnParseError> {
+        match self.rest().find(end) {
+            Some(offset) => {
+                self.pos += offset + end.len();
+                Ok(())
+            }
+            None => Err(PbnParseError::InvalidXml(self.pos)),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        let trimmed = self.rest().trim_start();
+        self.pos = self.text.len() - trimmed.len();
+    }
+
+    fn read_name(&mut self) -> Result<&'a str, PbnParseError> {
+        let rest = self.rest();
+        let end = rest.find(|c : char| c.is_whitespace() || c == '>' || c == '/' || c == '=').unwrap_or(rest.len());
+        if end == 0 {
+            return Err(PbnParseError::InvalidXml(self.pos));
+        }
+        self.pos += end;
+        Ok(&rest[..end])
+    }
+
+    //Skips declarations, doctype and comments, returns false at the end of the text
+    fn skip_misc(&mut self) -> Result<bool, PbnParseError> {
+        loop {
+            self.skip_whitespace();
+            let rest = self.rest();
+            if rest.is_empty() {
+                return Ok(false);
+            } else if rest.starts_with("<?") {
+                self.skip_until("?>")?;
+            } else if rest.starts_with("<!--") {
+                self.skip_until("-->")?;
+            } else if rest.starts_with("<!") {
+                self.skip_until(">")?;
+            } else {
+                return Ok(true);
+            }
+        }
+    }
+
+    //The reader is on the '<' of the start tag
+    fn read_element(&mut self) -> Result<XmlElement, PbnParseError> {
+        let open_pos = self.pos;
+        if !self.rest().starts_with('<') {
+            return Err(PbnParseError::InvalidXml(self.pos));
+        }
+        self.pos += 1;
+        let mut element = XmlElement::new(self.read_name()?);
+
+        loop {
+            self.skip_whitespace();
+            let rest = self.rest();
+            if rest.starts_with("/>") {
+                self.pos += 2;
+                return Ok(element);
+            } else if rest.starts_with('>') {
+                self.pos += 1;
+                break;
+            }
+            let name = self.read_name()?;
+            self.skip_whitespace();
+            if !self.rest().starts_with('=') {
+                return Err(PbnParseError::InvalidXml(self.pos));
+            }
+            self.pos += 1;
+            self.skip_whitespace();
+            let quote = match self.rest().chars().next() {
+                Some(quote) if quote == '"' || quote == '\'' => quote,
+                _ => return Err(PbnParseError::InvalidXml(self.pos)),
+            };
+            self.pos += 1;
+            let end = self.rest().find(quote).ok_or(PbnParseError::InvalidXml(self.pos))?;
+            let value = unescape_xml(&self.rest()[..end]);
+            element.attributes.push((name.to_string(), value));
+            self.pos += end + 1;
+        }
+
+        loop {
+            let rest = self.rest();
+            let text_end = rest.find('<').ok_or(PbnParseError::InvalidXml(self.pos))?;
+            element.text.push_str(&unescape_xml(&rest[..text_end]));
+            self.pos += text_end;
+            let rest = self.rest();
+            if rest.starts_with("</") {
+                let close_pos = self.pos;
+                self.pos += 2;
+                if self.read_name()? != element.name {
+                    return Err(PbnParseError::MismatchedTag(open_pos, close_pos));
+                }
+                self.skip_until(">")?;
+                return Ok(element);
+            } else if rest.starts_with("<!--") {
+                self.skip_until("-->")?;
+            } else if rest.starts_with("<![CDATA[") {
+                self.pos += "<![CDATA[".len();
+                let end = self.rest().find("]]>").ok_or(PbnParseError::InvalidXml(self.pos))?;
+                element.text.push_str(&self.rest()[..end]);
+                self.pos += end + 3;
+            } else {
+                element.children.push(self.read_element()?);
+            }
+        }
+    }
+}
+
+fn parse_xml(text : & str) -> Result<XmlElement, PbnParseError> {
+    let mut reader = XmlReader { text, pos: 0 };
+    if !reader.skip_misc()? {
+        return Err(PbnParseError::MissingElement("puzzle".to_string()));
+    }
+    let root = reader.read_element()?;
+    if reader.skip_misc()? {
+        return Err(PbnParseError::InvalidXml(reader.pos));
+    }
+    Ok(root)
+}
+
+fn read_clues(puzzle : & XmlElement, clue_type : & str, default_color : & str) -> Result<ClueArray, PbnParseError> {
+    let clues = puzzle.get_children("clues")
+        .find(|clues| clues.get_attribute("type") == Some(clue_type))
+        .ok_or_else(|| PbnParseError::MissingElement(format!("clues {}", clue_type)))?;
+    clues.get_children("line").map(|line| {
+        line.get_children("count").map(|count| {
+            if let Some(color) = count.get_attribute("color") {
+                if color != default_color {
+                    return Err(PbnParseError::UnsupportedColor(color.to_string()));
+                }
+            }
+            count.text.trim().parse().map_err(|_| PbnParseError::InvalidNumber(count.text.trim().to_string()))
+        }).collect()
+    }).collect()
+}
+
+//Each row of the image is written between '|', the characters come from the color elements
+fn read_solution(puzzle : & XmlElement, height : usize, width : usize, default_color : & str, background_color : & str) -> Result<Option<ImageBoard>, PbnParseError> {
+    let solution = puzzle.get_children("solution")
+        .find(|solution| solution.get_attribute("type").unwrap_or("goal") == "goal");
+    let image_text = match solution.and_then(|solution| solution.get_child("image")) {
+        Some(image) => &image.text,
+        None => return Ok(None),
+    };
+    let color_char = |name : & str, default : char| -> char {
+        puzzle.get_children("color")
+            .find(|color| color.get_attribute("name") == Some(name))
+            .and_then(|color| color.get_attribute("char"))
+            .and_then(|c| c.chars().next())
+            .unwrap_or(default)
+    };
+    let on_char = color_char(default_color, 'X');
+    let off_char = color_char(background_color, '.');
+
+    let rows : Vec<&str> = image_text.lines().map(|line| line.trim().trim_matches('|')).filter(|line| !line.is_empty()).collect();
+    if rows.len() != height {
+        return Err(PbnParseError::InvalidSolution);
+    }
+    let mut image = ImageBoard::new_empty(height, width);
+    for (y, row) in rows.iter().enumerate() {
+        let cells : Vec<char> = row.chars().collect();
+        if cells.len() != width {
+            return Err(PbnParseError::InvalidSolution);
+        }
+        for (x, c) in cells.iter().enumerate() {
+            let value = match *c {
+                c if c == on_char => CaseState::ON,
+                c if c == off_char => CaseState::OFF,
+                '?' => CaseState::UNKNOWN,
+                c => return Err(PbnParseError::UnsupportedColor(c.to_string())),
+            };
+            image.set_value(x, y, value);
+        }
+    }
+    Ok(Some(image))
+}
+
+const PBN_METADATA_ELEMENTS : [&str; 5] = ["source", "id", "copyright", "description", "note"];
+
+fn picross_from_pbn_puzzle(puzzle : & XmlElement) -> Result<PicrossBoard, PbnParseError> {
+    let default_color = puzzle.get_attribute("defaultcolor").unwrap_or("black");
+    let background_color = puzzle.get_attribute("backgroundcolor").unwrap_or("white");
+    let rows = read_clues(puzzle, "rows", default_color)?;
+    let columns = read_clues(puzzle, "columns", default_color)?;
+
+    let mut picross = PicrossBoard::new_empty(rows.len(), columns.len());
+    if let Some(image) = read_solution(puzzle, rows.len(), columns.len(), default_color, background_color)? {
+        picross.image = image;
+    }
+    picross.left = ClueBoard::new_from_clue_array(Orientation::Vertical, rows);
+    picross.top = ClueBoard::new_from_clue_array(Orientation::Horizontal, columns);
+
+    let text_of = |name : & str| puzzle.get_child(name).map(|child| child.text.trim().to_string());
+    picross.metadata = PicrossMetadata {
+        title: text_of("title"),
+        author: text_of("author"),
+        extra: PBN_METADATA_ELEMENTS.iter()
+            .filter_map(|&name| text_of(name).map(|text| (name.to_string(), text)))
+            .collect(),
+    };
+    Ok(picross)
+}
+
+//Every puzzle of a puzzleset, or the single puzzle of the file
+pub fn picross_list_from_pbn_string(text : & str) -> Result<Vec<PicrossBoard>, PbnParseError> {
+    let root = parse_xml(text)?;
+    if root.name == "puzzle" {
+        return Ok(vec![picross_from_pbn_puzzle(&root)?]);
+    }
+    let puzzles : Vec<&XmlElement> = root.get_children("puzzle").collect();
+    if puzzles.is_empty() {
+        return Err(PbnParseError::MissingElement("puzzle".to_string()));
+    }
+    puzzles.into_iter().map(picross_from_pbn_puzzle).collect()
+}
+
+pub fn picross_from_pbn_string(text : & str) -> Result<PicrossBoard, PbnParseError> {
+    picross_list_from_pbn_string(text).map(|mut puzzles| puzzles.remove(0))
+}
+
+fn clues_to_pbn(clue_type : & str, values : & ClueArray) -> XmlElement {
+    let mut clues = XmlElement::new("clues").with_attribute("type", clue_type);
+    for line in values.iter() {
+        let mut element = XmlElement::new("line");
+        for clue in line.iter().filter(|&&clue| clue > 0) {
+            element.children.push(XmlElement::new("count").with_text(&clue.to_string()));
+        }
+        clues.children.push(element);
+    }
+    clues
+}
+
+//The solution is only written when every cell of the image is known, extra metadata is written for the pbn elements only
+pub fn picross_to_pbn_string(picross : & PicrossBoard) -> String {
+    let mut puzzle = XmlElement::new("puzzle").with_attribute("type", "grid").with_attribute("defaultcolor", "black");
+    if let Some(title) = &picross.metadata.title {
+        puzzle.children.push(XmlElement::new("title").with_text(title));
+    }
+    if let Some(author) = &picross.metadata.author {
+        puzzle.children.push(XmlElement::new("author").with_text(author));
+    }
+    for (key, value) in picross.metadata.extra.iter() {
+        if PBN_METADATA_ELEMENTS.contains(&key.as_str()) {
+            puzzle.children.push(XmlElement::new(key).with_text(value));
+        }
+    }
+    puzzle.children.push(XmlElement::new("color").with_attribute("name", "white").with_attribute("char", ".").with_text("fff"));
+    puzzle.children.push(XmlElement::new("color").with_attribute("name", "black").with_attribute("char", "X").with_text("000"));
+    puzzle.children.push(clues_to_pbn("columns", picross.top.get_clue_array()));
+    puzzle.children.push(clues_to_pbn("rows", picross.left.get_clue_array()));
+
+    let image = &picross.image;
+    let rows : Option<Vec<String>> = (0..image.height()).map(|y| {
+        (0..image.width()).map(|x| match image.get_value(x, y) {
+            CaseState::ON => Some('X'),
+            CaseState::OFF => Some('.'),
+            CaseState::UNKNOWN => None,
+        }).collect()
+    }).collect();
+    if let Some(rows) = rows {
+        let image_text : String = rows.iter().map(|row| format!("|{}|\n", row)).collect();
+        let mut solution = XmlElement::new("solution").with_attribute("type", "goal");
+        solution.children.push(XmlElement::new("image").with_text(&format!("\n{}", image_text)));
+        puzzle.children.push(solution);
+    }
+
+    let mut puzzle_set = XmlElement::new("puzzleset");
+    puzzle_set.children.push(puzzle);
+    let mut text = String::from("<?xml version=\"1.0\"?>\n<!DOCTYPE pbn SYSTEM \"https://webpbn.com/pbn-0.3.dtd\">\n");
+    puzzle_set.write(&mut text, Some(0));
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PBN_SAMPLE : &str = r##"<?xml version="1.0"?>
+<!DOCTYPE pbn SYSTEM "https://webpbn.com/pbn-0.3.dtd">
+<puzzleset>
+<puzzle type="grid" defaultcolor="black">
+<source>local</source>
+<title>Corner &amp; bar</title>
+<author>Someone</author>
+<!-- a comment -->
+<color name="white" char=".">fff</color>
+<color name="black" char="#">000</color>
+<clues type="columns">
+<line><count>2</count></line>
+<line><count>1</count></line>
+<line/>
+</clues>
+<clues type="rows">
+<line><count>2</count></line>
+<line><count>1</count></line>
+</clues>
+<solution type="goal">
+<image>
+|##.|
+|#..|
+</image>
+</solution>
+</puzzle>
+</puzzleset>
+"##;
+
+    #[test]
+    fn test_pbn_read() {
+        let picross = picross_from_pbn_string(PBN_SAMPLE).unwrap();
+        assert_eq!(picross.height(), 2);
+        assert_eq!(picross.width(), 3);
+        assert_eq!(picross.top.get_line_copy(0), vec![2]);
+        assert!(picross.top.get_line_copy(2).is_empty());
+        assert_eq!(picross.left.get_line_copy(1), vec![1]);
+        assert!(picross.image.get_value(1, 0) == CaseState::ON);
+        assert!(picross.image.get_value(1, 1) == CaseState::OFF);
+        assert_eq!(picross.metadata.title.as_deref(), Some("Corner & bar"));
+        assert_eq!(picross.metadata.author.as_deref(), Some("Someone"));
+        assert_eq!(picross.metadata.extra, vec![("source".to_string(), "local".to_string())]);
+    }
+
+    #[test]
+    fn test_pbn_round_trip() {
+        let picross = picross_from_pbn_string(PBN_SAMPLE).unwrap();
+        let text = picross_to_pbn_string(&picross);
+        let parsed = picross_from_pbn_string(&text).unwrap();
+        assert_eq!(parsed.metadata, picross.metadata);
+        assert!(parsed.image == picross.image);
+        assert_eq!(parsed.to_clue_string(), picross.to_clue_string());
+        assert_eq!(picross_to_pbn_string(&parsed), text);
+
+        let picross = PicrossBoard::picross_from_clue_string(CLUE_STRING_12X12).unwrap();
+        let parsed = picross_from_pbn_string(&picross_to_pbn_string(&picross)).unwrap();
+        assert_eq!(parsed.top.get_clue_array(), picross.top.get_clue_array());
+        assert_eq!(parsed.left.get_clue_array(), picross.left.get_clue_array());
+    }
+
+    #[test]
+    fn test_pbn_errors() {
+        assert_eq!(picross_from_pbn_string("<puzzle><clues type=\"rows\"></clues></puzzle>").err(), Some(PbnParseError::MissingElement("clues columns".to_string())));
+        assert_eq!(picross_from_pbn_string("<puzzle><title>a</author></puzzle>").err(), Some(PbnParseError::MismatchedTag(8, 16)));
+        assert_eq!(picross_from_pbn_string("<puzzle><title>a</title>").err(), Some(PbnParseError::InvalidXml(24)));
+        let colored = "<puzzle><clues type=\"rows\"><line><count color=\"red\">1</count></line></clues><clues type=\"columns\"><line/></clues></puzzle>";
+        assert_eq!(picross_from_pbn_string(colored).err(), Some(PbnParseError::UnsupportedColor("red".to_string())));
+        let bad_count = "<puzzle><clues type=\"rows\"><line><count>x</count></line></clues><clues type=\"columns\"/></puzzle>";
+        assert_eq!(picross_from_pbn_string(bad_count).err(), Some(PbnParseError::InvalidNumber("x".to_string())));
+    }
+}
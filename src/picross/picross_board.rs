@@ -1,10 +1,61 @@
 use super::*;
 
-#[derive(Debug)]
+//JSON schema with the serde feature, names are stable :
+//  PicrossBoard   {"left": ClueBoard, "top": ClueBoard, "image": ImageBoard, "metadata": PicrossMetadata}
+//                 metadata can be left out, left holds the vertical row clues and top the horizontal column clues
+//  ClueBoard      {"orientation": Orientation, "clues": [[usize]]}, one list per line, [] or [0] for an empty line
+//  ImageBoard     {"height": usize, "width": usize, "values": [CaseState]}, row by row, height * width values
+//  PicrossMetadata {"title": string or null, "author": string or null, "extra": [[key, value]]}
+//  CaseState      "unknown", "on" or "off"
+//  Orientation    "vertical" or "horizontal"
+//  Solve          "none", "partial", "full", "unknown", "contradiction" or "exhausted"
+//  Contradiction  {"line": [Orientation, usize]}
+
+//Descriptive fields of puzzle files, the solver ignores them
+#[derive(Clone, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PicrossMetadata {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    //Other header lines of the file, as key and raw value, kept to be written back
+    pub extra: Vec<(String, String)>,
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(try_from = "PicrossBoardFields"))]
 pub struct PicrossBoard {
     pub left: ClueBoard,
     pub top: ClueBoard,
     pub image: ImageBoard,
+    pub metadata: PicrossMetadata,
+}
+
+//Same fields as PicrossBoard, only accepted when the clues are oriented as rows on the left and columns
+//on the top and match the size of the image
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct PicrossBoardFields {
+    left: ClueBoard,
+    top: ClueBoard,
+    image: ImageBoard,
+    #[serde(default)]
+    metadata: PicrossMetadata,
+}
+
+#[cfg(feature = "serde")]
+impl std::convert::TryFrom<PicrossBoardFields> for PicrossBoard {
+    type Error = String;
+
+    fn try_from(fields : PicrossBoardFields) -> Result<Self, Self::Error> {
+        if fields.left.get_orientation() != Orientation::Vertical || fields.top.get_orientation() != Orientation::Horizontal {
+            return Err("left clues must be vertical and top clues horizontal".to_string());
+        }
+        if fields.left.get_size() != fields.image.height() || fields.top.get_size() != fields.image.width() {
+            return Err(format!("{} rows and {} columns of clues for a {}x{} image",
+                fields.left.get_size(), fields.top.get_size(), fields.image.width(), fields.image.height()));
+        }
+        Ok(Self { left: fields.left, top: fields.top, image: fields.image, metadata: fields.metadata })
+    }
 }
 
 impl PicrossBoard {
@@ -13,20 +64,27 @@ impl PicrossBoard {
             left: ClueBoard::new_empty(Orientation::Vertical, height),
             top: ClueBoard::new_empty(Orientation::Horizontal, width),
             image: ImageBoard::new_empty(height, width),
+            metadata: PicrossMetadata::default(),
         }
     }
 
-    pub fn picross_from_clue_string( clue_string : & String) -> Self {
-         let (top_array, left_array) = clue_boards_from_clue_string(clue_string);
+    pub fn picross_from_clue_string( clue_string : & str) -> Result<Self, ClueParseError> {
+         let (top_array, left_array) = clue_boards_from_clue_string(clue_string)?;
          let top = ClueBoard::new_from_clue_array(Orientation::Horizontal, top_array);
          let left = ClueBoard::new_from_clue_array(Orientation::Vertical, left_array);
          let height = left.get_size();
          let width = top.get_size();
-         Self {
+         Ok(Self {
              left,
              top,
-             image: ImageBoard::new_empty(height, width)
-         }
+             image: ImageBoard::new_empty(height, width),
+             metadata: PicrossMetadata::default(),
+         })
+    }
+
+    //The compact format read by picross_from_clue_string
+    pub fn to_clue_string(&self) -> String {
+        clue_string_from_clue_boards(self.top.get_clue_array(), self.left.get_clue_array())
     }
 
     pub fn new_from_image(image: &ImageBoard) -> Self {
@@ -34,6 +92,7 @@ impl PicrossBoard {
             left: ClueBoard::new_from_image(Orientation::Vertical, image),
             top: ClueBoard::new_from_image(Orientation::Horizontal, image),
             image: ImageBoard::new_empty(image.height(), image.width()),
+            metadata: PicrossMetadata::default(),
         }
     }
 
@@ -65,4 +124,78 @@ mod tests {
         picross.top.push_value(2,1);
         assert_eq!(picross.top.get_value(2,0), 1);
     }
+
+    #[test]
+    fn test_picross_from_invalid_clue_string() {
+        let picross = PicrossBoard::picross_from_clue_string("T1,2L2,1").unwrap();
+        assert_eq!(picross.height(), 2);
+        assert_eq!(picross.width(), 2);
+        assert!(PicrossBoard::picross_from_clue_string("T1,2").err() == Some(ClueParseError::MissingLeft));
+    }
+
+    #[test]
+    fn test_clue_string_round_trip() {
+        for seed in 0..50 {
+            let image = create_random_image_board(3 + (seed as usize) % 12, 2 + (seed as usize) % 17, seed);
+            let picross = PicrossBoard::new_from_image(&image);
+            let parsed = PicrossBoard::picross_from_clue_string(&picross.to_clue_string()).unwrap();
+            assert_eq!(parsed.height(), picross.height());
+            assert_eq!(parsed.width(), picross.width());
+            assert_eq!(parsed.to_clue_string(), picross.to_clue_string());
+            for y in 0..picross.height() {
+                let line = picross.left.get_line_copy(y);
+                assert!(parsed.left.get_line_copy(y) == line || (line.is_empty() && parsed.left.get_line_copy(y) == vec![0]));
+            }
+        }
+
+        for sample in [CLUE_STRING_10X10, CLUE_STRING_12X12, CLUE_STRING_30X30].iter() {
+            let picross = PicrossBoard::picross_from_clue_string(sample).unwrap();
+            let parsed = PicrossBoard::picross_from_clue_string(&picross.to_clue_string()).unwrap();
+            assert_eq!(parsed.top.get_clue_array(), picross.top.get_clue_array());
+            assert_eq!(parsed.left.get_clue_array(), picross.left.get_clue_array());
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_picross_json_schema() {
+        let mut picross = PicrossBoard::picross_from_clue_string("T1,0L1").unwrap();
+        picross.image.set_value(0, 0, CaseState::ON);
+        picross.metadata.title = Some("Dot".to_string());
+        let json = serde_json::to_string(&picross).unwrap();
+        assert_eq!(json, concat!(
+            r#"{"left":{"orientation":"vertical","clues":[[1]]},"top":{"orientation":"horizontal","clues":[[1],[0]]},"#,
+            r#""image":{"height":1,"width":2,"values":["on","unknown"]},"metadata":{"title":"Dot","author":null,"extra":[]}}"#));
+
+        let parsed : PicrossBoard = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.to_clue_string(), picross.to_clue_string());
+        assert!(parsed.image == picross.image);
+        assert_eq!(parsed.metadata, picross.metadata);
+
+        let without_metadata = r#"{"left":{"orientation":"vertical","clues":[[1]]},"top":{"orientation":"horizontal","clues":[[1]]},"image":{"height":1,"width":1,"values":["off"]}}"#;
+        let parsed : PicrossBoard = serde_json::from_str(without_metadata).unwrap();
+        assert_eq!(parsed.metadata, PicrossMetadata::default());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_picross_json_invalid_sizes() {
+        let image = r#"{"height":2,"width":2,"values":["on"]}"#;
+        assert!(serde_json::from_str::<ImageBoard>(image).is_err());
+        let image = r#"{"height":18446744073709551615,"width":2,"values":[]}"#;
+        assert!(serde_json::from_str::<ImageBoard>(image).is_err());
+        let picross = r#"{"left":{"orientation":"vertical","clues":[[1],[1]]},"top":{"orientation":"horizontal","clues":[[1]]},"image":{"height":1,"width":1,"values":["off"]}}"#;
+        assert!(serde_json::from_str::<PicrossBoard>(picross).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_picross_json_invalid_orientations() {
+        let picross = r#"{"left":{"orientation":"horizontal","clues":[[1]]},"top":{"orientation":"horizontal","clues":[[1]]},"image":{"height":1,"width":1,"values":["on"]}}"#;
+        assert!(serde_json::from_str::<PicrossBoard>(picross).is_err());
+        let picross = r#"{"left":{"orientation":"vertical","clues":[[1]]},"top":{"orientation":"vertical","clues":[[1]]},"image":{"height":1,"width":1,"values":["on"]}}"#;
+        assert!(serde_json::from_str::<PicrossBoard>(picross).is_err());
+        let picross = r#"{"left":{"orientation":"vertical","clues":[[1]]},"top":{"orientation":"horizontal","clues":[[1]]},"image":{"height":1,"width":1,"values":["on"]}}"#;
+        assert!(serde_json::from_str::<PicrossBoard>(picross).is_ok());
+    }
 }
@@ -1,5 +1,6 @@
 use super::*;
 
+#[derive(Clone)]
 pub struct PicrossLineUnit {
     pub clues: ClueLine,
     pub values: Vec<CaseState>
@@ -21,13 +22,9 @@ impl PicrossLineUnit {
         self.values[idx] = val;
     }
 
-    pub fn free_space_count(&self) -> usize {
-        let sum_of_occupied : usize = self.clues.iter().sum::<usize>() + self.clues.len() - 1;
-        let total_space = self.values.len();
-        if sum_of_occupied > total_space {
-            panic!("This line is to small for the given clues");
-        }
-        total_space - sum_of_occupied
+    //None when the line is too small for the given clues
+    pub fn free_space_count(&self) -> Option<usize> {
+        get_min_line_size(&self.clues).and_then(|occupied| self.values.len().checked_sub(occupied))
     }
 }
 
@@ -36,8 +33,6 @@ mod tests {
     use super::*;
     use crate::image::CaseState::*;
 
-    use std::panic;
-
     #[test]
     fn test_picross_line_unit() {
         let clues: ClueLine = vec![1,1];
@@ -53,35 +48,40 @@ mod tests {
     }
 
     #[test]
-    #[should_panic]
     fn test_space_compute() {
         let clues: ClueLine = vec![0];
         let size: usize = 0;
         let picross = PicrossLineUnit::new(size, clues);
-        assert_eq!(picross.free_space_count(), 0);
+        assert_eq!(picross.free_space_count(), Some(0));
+
+        let clues: ClueLine = vec![];
+        let size: usize = 2;
+        let picross = PicrossLineUnit::new(size, clues);
+        assert_eq!(picross.free_space_count(), Some(2));
 
         let clues: ClueLine = vec![5];
         let size: usize = 5;
         let picross = PicrossLineUnit::new(size, clues);
-        assert_eq!(picross.free_space_count(), 0);
+        assert_eq!(picross.free_space_count(), Some(0));
 
         let clues: ClueLine = vec![2,1,2];
         let size: usize = 7;
         let picross = PicrossLineUnit::new(size, clues);
-        assert_eq!(picross.free_space_count(), 0);
+        assert_eq!(picross.free_space_count(), Some(0));
 
         let clues: ClueLine = vec![2,1,2];
         let size: usize = 10;
         let picross = PicrossLineUnit::new(size, clues);
-        assert_eq!(picross.free_space_count(), 3);
+        assert_eq!(picross.free_space_count(), Some(3));
 
         let clues: ClueLine = vec![2,1,2];
         let size: usize = 6;
         let picross = PicrossLineUnit::new(size, clues);
+        assert_eq!(picross.free_space_count(), None);
 
-        panic::set_hook(Box::new(|_| {
-            println!("Intended Panic Happened");
-        }));
-        picross.free_space_count();
+        let clues: ClueLine = vec![usize::MAX, 1];
+        let size: usize = 4;
+        let picross = PicrossLineUnit::new(size, clues);
+        assert_eq!(picross.free_space_count(), None);
     }
 }
\ No newline at end of file
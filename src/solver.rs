@@ -3,10 +3,22 @@ use super::*;
 pub use picross::*;
 
 pub mod solvable;
+pub mod solve_budget;
+pub mod solve_observer;
 pub mod clue_index_range;
 pub mod invalidator;
 pub mod validator;
 pub mod one_line_solver;
+pub mod dynamic_line_solver;
+pub mod line_cache;
 pub mod row_col_picross_solver;
+pub mod backtrack_solver;
+pub mod solution_counter;
+pub mod ambiguity_report;
+pub mod solve_trace;
+pub mod hint;
+pub mod difficulty;
+pub mod sat_encoding;
+pub mod oracle_solver;
 
-pub use crate::solver::{solvable::*, clue_index_range::*, invalidator::*, validator::*, one_line_solver::*, row_col_picross_solver::*};
\ No newline at end of file
+pub use crate::solver::{solvable::*, solve_budget::*, solve_observer::*, clue_index_range::*, invalidator::*, validator::*, one_line_solver::*, dynamic_line_solver::*, line_cache::*, row_col_picross_solver::*, backtrack_solver::*, solution_counter::*, ambiguity_report::*, solve_trace::*, hint::*, difficulty::*, sat_encoding::*, oracle_solver::*};
\ No newline at end of file
@@ -0,0 +1,214 @@
+use super::*;
+
+use std::cmp;
+use std::collections::VecDeque;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct AmbiguityRegion {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl AmbiguityRegion {
+    pub fn contains(&self, x : usize, y : usize) -> bool {
+        x >= self.x && x < self.x + self.width && y >= self.y && y < self.y + self.height
+    }
+}
+
+pub struct AmbiguityReport {
+    pub reference: ImageBoard,
+    pub ambiguous_cells: Vec<(usize, usize)>,
+    pub regions: Vec<AmbiguityRegion>,
+}
+
+fn get_bounding_region(cells : & [(usize, usize)]) -> AmbiguityRegion {
+    let left = cells.iter().map(|&(x, _)| x).min().unwrap();
+    let top = cells.iter().map(|&(_, y)| y).min().unwrap();
+    let right = cells.iter().map(|&(x, _)| x).max().unwrap();
+    let bottom = cells.iter().map(|&(_, y)| y).max().unwrap();
+    AmbiguityRegion { x: left, y: top, width: right - left + 1, height: bottom - top + 1 }
+}
+
+//A differing cell is an edge between its row and its column, going from the row to the column when
+//the reference cell is ON and back otherwise. Both solutions have the same count of ON cells on every
+//line, so each line has as many edges going in as out and the cells split into cycles.
+//A cycle is the smallest group of cells balancing all its rows and columns: one region per cycle.
+fn get_swap_regions(cells : & [(usize, usize)], reference : & ImageBoard) -> Vec<AmbiguityRegion> {
+    let height = reference.height();
+    let get_ends = |(x, y) : (usize, usize)| match reference.get_value(x, y) {
+        CaseState::ON => (y, height + x),
+        _ => (height + x, y),
+    };
+    let mut out_edges : Vec<Vec<usize>> = vec![Vec::new(); height + reference.width()];
+    for (i, &cell) in cells.iter().enumerate() {
+        out_edges[get_ends(cell).0].push(i);
+    }
+
+    let mut remaining = vec![true; cells.len()];
+    let mut regions = Vec::new();
+    for start in 0..cells.len() {
+        if !remaining[start] {
+            continue;
+        }
+        remaining[start] = false;
+        let (from, to) = get_ends(cells[start]);
+
+        //Shortest way back from the end of the first edge to its start
+        let mut previous : Vec<Option<usize>> = vec![None; out_edges.len()];
+        let mut queue = VecDeque::new();
+        queue.push_back(to);
+        while let Some(node) = queue.pop_front() {
+            if node == from {
+                break;
+            }
+            for &i in out_edges[node].iter() {
+                let next = get_ends(cells[i]).1;
+                if remaining[i] && next != to && previous[next].is_none() {
+                    previous[next] = Some(i);
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        let mut group = vec![cells[start]];
+        let mut node = from;
+        while node != to {
+            match previous[node] {
+                Some(i) => {
+                    remaining[i] = false;
+                    group.push(cells[i]);
+                    node = get_ends(cells[i]).0;
+                }
+                //Unbalanced lines cannot happen between two solutions, the cell stays alone
+                None => break,
+            }
+        }
+        regions.push(get_bounding_region(&group));
+    }
+    regions
+}
+
+impl AmbiguityReport {
+    //The first solution is the reference the other ones are compared to, None without any solution
+    pub fn new(solutions : & [ImageBoard]) -> Option<Self> {
+        let (reference, others) = solutions.split_first()?;
+        let reference = reference.clone();
+        let mut ambiguous_cells = Vec::new();
+        let mut regions = Vec::new();
+        for solution in others.iter() {
+            let mut cells = Vec::new();
+            for y in 0..reference.height() {
+                for x in 0..reference.width() {
+                    if solution.get_value(x, y) != reference.get_value(x, y) {
+                        cells.push((x, y));
+                    }
+                }
+            }
+            for region in get_swap_regions(&cells, &reference) {
+                if !regions.contains(&region) {
+                    regions.push(region);
+                }
+            }
+            ambiguous_cells.extend(cells);
+        }
+        ambiguous_cells.sort_by_key(|&(x, y)| (y, x));
+        ambiguous_cells.dedup();
+
+        Some(Self {
+            reference,
+            ambiguous_cells,
+            regions,
+        })
+    }
+
+    pub fn is_ambiguous(&self, x : usize, y : usize) -> bool {
+        self.ambiguous_cells.contains(&(x, y))
+    }
+}
+
+//None when the picross has less than two solutions
+pub fn report_picross_ambiguity(picross : & PicrossBoard, method : LineSolveMethod, limit : usize) -> Option<AmbiguityReport> {
+    let solutions = find_picross_solutions(picross, method, cmp::max(limit, 2));
+    if solutions.len() < 2 {
+        return None;
+    }
+    AmbiguityReport::new(&solutions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::image::CaseState::*;
+
+    fn image_from_values(height : usize, width : usize, values : & [CaseState]) -> ImageBoard {
+        let mut image = ImageBoard::new_empty(height, width);
+        for (i, value) in values.iter().enumerate() {
+            image.set_value(i % width, i / width, *value);
+        }
+        image
+    }
+
+    #[test]
+    fn test_ambiguity_single_swap() {
+        let picross = PicrossBoard::picross_from_clue_string("T1,1L1,1").unwrap();
+        let report = report_picross_ambiguity(&picross, LineSolveMethod::Dynamic, 2).unwrap();
+        assert_eq!(report.ambiguous_cells, vec![(0,0), (1,0), (0,1), (1,1)]);
+        assert_eq!(report.regions, vec![AmbiguityRegion { x: 0, y: 0, width: 2, height: 2 }]);
+    }
+
+    #[test]
+    fn test_ambiguity_separate_swaps() {
+        let first = image_from_values(4, 4, &[
+            ON, OFF, OFF, OFF,
+            OFF, ON, OFF, OFF,
+            OFF, OFF, ON, OFF,
+            OFF, OFF, OFF, ON,
+        ]);
+        let second = image_from_values(4, 4, &[
+            OFF, ON, OFF, OFF,
+            ON, OFF, OFF, OFF,
+            OFF, OFF, OFF, ON,
+            OFF, OFF, ON, OFF,
+        ]);
+        let report = AmbiguityReport::new(&[first, second]).unwrap();
+        assert_eq!(report.ambiguous_cells.len(), 8);
+        assert_eq!(report.regions, vec![
+            AmbiguityRegion { x: 0, y: 0, width: 2, height: 2 },
+            AmbiguityRegion { x: 2, y: 2, width: 2, height: 2 },
+        ]);
+        assert!(report.is_ambiguous(3, 2));
+        assert!(!report.is_ambiguous(0, 3));
+        assert!(report.regions[1].contains(3, 3));
+    }
+
+    #[test]
+    fn test_ambiguity_swaps_sharing_a_row() {
+        let first = image_from_values(3, 4, &[
+            ON, OFF, ON, OFF,
+            OFF, ON, OFF, OFF,
+            OFF, OFF, OFF, ON,
+        ]);
+        let second = image_from_values(3, 4, &[
+            OFF, ON, OFF, ON,
+            ON, OFF, OFF, OFF,
+            OFF, OFF, ON, OFF,
+        ]);
+        let report = AmbiguityReport::new(&[first, second]).unwrap();
+        assert_eq!(report.ambiguous_cells.len(), 8);
+        assert_eq!(report.regions, vec![
+            AmbiguityRegion { x: 0, y: 0, width: 2, height: 2 },
+            AmbiguityRegion { x: 2, y: 0, width: 2, height: 3 },
+        ]);
+        assert!(!report.regions[1].contains(1, 1));
+    }
+
+    #[test]
+    fn test_no_ambiguity_report_for_unique_picross() {
+        let picross = PicrossBoard::picross_from_clue_string("T1,3,1L1,3,1").unwrap();
+        assert!(report_picross_ambiguity(&picross, LineSolveMethod::Dynamic, 2).is_none());
+        assert!(AmbiguityReport::new(&[]).is_none());
+    }
+}
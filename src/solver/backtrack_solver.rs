@@ -0,0 +1,161 @@
+use super::*;
+
+use std::cmp;
+
+pub struct BacktrackSolver {
+    pub picross: RowColPicross,
+    pub node_count: usize,
+    pub max_depth: usize,
+    //The budget of the picross ran out, the search stopped before the end
+    pub exhausted: bool,
+}
+
+//The unknown cell of the row with the fewest unknown cells, the most constrained guess
+pub fn choose_unknown_cell(picross : & RowColPicross) -> Option<(usize, usize)> {
+    let mut best : Option<(usize, usize, usize)> = None;
+    for y in 0..picross.height() {
+        let row = picross.get_row(y);
+        let unknown_count = (0..row.size()).filter(|&x| row.get(x) == CaseState::UNKNOWN).count();
+        let is_better = match best {
+            Some((best_count, _, _)) => unknown_count < best_count,
+            None => true,
+        };
+        if unknown_count > 0 && is_better {
+            let x = (0..row.size()).position(|x| row.get(x) == CaseState::UNKNOWN).unwrap();
+            best = Some((unknown_count, x, y));
+        }
+    }
+    best.map(|(_, x, y)| (x, y))
+}
+
+impl BacktrackSolver {
+    pub fn new(picross : RowColPicross) -> Self {
+        Self {
+            picross,
+            node_count: 0,
+            max_depth: 0,
+            exhausted: false,
+        }
+    }
+
+    fn search(&mut self, mut picross : RowColPicross, depth : usize) -> Option<RowColPicross> {
+        self.node_count += 1;
+        self.max_depth = cmp::max(self.max_depth, depth);
+
+        match picross.solve() {
+            Solve::Full => return Some(picross),
+            Solve::Partial => {},
+            Solve::Exhausted => {
+                //The deductions of the first level are kept, guesses are not
+                self.exhausted = true;
+                if depth == 0 {
+                    self.picross = picross;
+                }
+                return None;
+            }
+            _ => {
+                //The first line found wrong is kept, when every branch fails it is the one reported
+                if self.picross.contradiction.is_none() {
+                    self.picross.contradiction = picross.contradiction;
+                }
+                return None;
+            }
+        }
+
+        let (x, y) = choose_unknown_cell(&picross)?;
+        for &state in [CaseState::ON, CaseState::OFF].iter() {
+            let mut branch = picross.clone();
+            branch.set_value(x, y, state);
+            notify_observer(&picross.observer, |observer| observer.branch_pushed(x, y, state, depth + 1));
+            let solved = self.search(branch, depth + 1);
+            notify_observer(&picross.observer, |observer| observer.branch_popped(depth + 1));
+            if solved.is_some() {
+                return solved;
+            }
+            if self.exhausted {
+                break;
+            }
+        }
+        if self.exhausted && depth == 0 {
+            self.picross = picross;
+        }
+        None
+    }
+}
+
+impl Solvable for BacktrackSolver {
+    fn solve(&mut self) -> Solve {
+        match self.search(self.picross.clone(), 0) {
+            Some(solved) => {
+                self.picross = solved;
+                Solve::Full
+            }
+            None if self.exhausted => Solve::Exhausted,
+            None => Solve::Contradiction,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backtrack_ambiguous_picross() {
+        let picross_base = PicrossBoard::picross_from_clue_string("T1,1L1,1").unwrap();
+        let mut picross = RowColPicross::new(&picross_base);
+        picross.line_solve_method = LineSolveMethod::Dynamic;
+        assert!(picross.clone().solve() == Solve::Partial);
+
+        let mut solver = BacktrackSolver::new(picross);
+        assert!(solver.solve() == Solve::Full);
+        assert_eq!(solver.picross.unknown_count(), 0);
+        assert!(solver.max_depth >= 1);
+    }
+
+    #[test]
+    fn test_backtrack_impossible_picross() {
+        let picross_base = PicrossBoard::picross_from_clue_string("T2,2L1,1").unwrap();
+        let picross = RowColPicross::new(&picross_base);
+        let mut solver = BacktrackSolver::new(picross);
+        assert!(solver.solve() == Solve::Contradiction);
+        assert!(solver.picross.contradiction.is_some());
+    }
+
+    #[test]
+    fn test_backtrack_impossible_after_branching() {
+        let picross_base = PicrossBoard::picross_from_clue_string("T1,0,1L1,1,1").unwrap();
+        let mut picross = RowColPicross::new(&picross_base);
+        picross.line_solve_method = LineSolveMethod::Dynamic;
+        assert!(picross.clone().solve() == Solve::Partial);
+
+        let mut solver = BacktrackSolver::new(picross);
+        assert!(solver.solve() == Solve::Contradiction);
+        assert!(solver.max_depth >= 1);
+        assert!(matches!(solver.picross.contradiction, Some(Contradiction::Line(_, _))));
+    }
+
+    #[test]
+    fn test_backtrack_random_images() {
+        for seed in 0..20 {
+            let image = create_random_image_board(8, 8, seed);
+            let mut picross = PicrossBoard::new_from_image(&image);
+            assert!(solve_picross_board(&mut picross, LineSolveMethod::Dynamic) == Solve::Full);
+            assert!(validate_picross(&mut picross));
+        }
+    }
+
+    #[test]
+    fn test_backtrack_exhausted_budget() {
+        let picross_base = PicrossBoard::picross_from_clue_string("T1,1L1,1").unwrap();
+        let mut picross = RowColPicross::new(&picross_base);
+        picross.line_solve_method = LineSolveMethod::Dynamic;
+        picross.budget = SolveBudget::new(SolveOptions::new().with_max_line_tries(4));
+
+        let mut solver = BacktrackSolver::new(picross);
+        assert!(solver.solve() == Solve::Exhausted);
+        assert!(solver.exhausted);
+        assert!(solver.picross.contradiction.is_none());
+        assert_eq!(solver.picross.unknown_count(), 4);
+    }
+}
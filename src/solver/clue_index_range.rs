@@ -14,15 +14,15 @@ fn get_packed_line(clues : & ClueLine, line_size : usize, packing : Direction) -
     if packing == Direction::Forward {
         let mut current_clue = 0;
         let mut current_counter = 0;
-        for i in 0..line_size {
-            indices[i] = current_clue;
+        for index in indices.iter_mut() {
+            *index = current_clue;
             current_counter += 1;
             if current_clue < clues.len()-1 && current_counter == clues[current_clue] + 1 {
                 current_clue += 1;
                 current_counter = 0;
             }
         }
-        return indices;
+        indices
     } else {
         let mut current_clue = clues.len() - 1;
         let mut current_counter = 0;
@@ -34,19 +34,22 @@ fn get_packed_line(clues : & ClueLine, line_size : usize, packing : Direction) -
                 current_counter = 0;
             }
         }
-        return indices;
+        indices
     }
     
 }
 
 pub fn get_clue_index_range(clues : & ClueLine, line_size : usize) -> ClueIndexRange {
-    let forward_range : PackedIndices = get_packed_line(& clues, line_size, Direction::Forward);
-    let backward_range : PackedIndices = get_packed_line(& clues, line_size, Direction::Backward);
+    if clues.is_empty() {
+        return vec![(0,0); line_size];
+    }
+    let forward_range : PackedIndices = get_packed_line(clues, line_size, Direction::Forward);
+    let backward_range : PackedIndices = get_packed_line(clues, line_size, Direction::Backward);
     let mut range = vec![(0,0); line_size];
     for i in 0..line_size {
         range[i] = (std::cmp::min(forward_range[i], backward_range[i]), std::cmp::max(forward_range[i], backward_range[i]));
     }
-    return range;
+    range
 }
 
 #[cfg(test)]
@@ -0,0 +1,130 @@
+use super::*;
+
+#[derive(Clone, Copy, PartialEq, PartialOrd, Debug)]
+pub enum DifficultyTier {
+    Easy,
+    Medium,
+    Hard,
+    Expert,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Difficulty {
+    pub score: usize,
+    pub tier: DifficultyTier,
+    //Row then column passes of the line propagation
+    pub iteration_count: usize,
+    //Only overlaps of blocks and empty lines were needed
+    pub overlap_only: bool,
+    //Line propagation alone was not enough, cells had to be guessed
+    pub search_needed: bool,
+    pub search_node_count: usize,
+    pub search_depth: usize,
+}
+
+const ITERATION_WEIGHT : usize = 5;
+const REASON_WEIGHT : usize = 10;
+const SEARCH_WEIGHT : usize = 100;
+const SEARCH_NODE_WEIGHT : usize = 2;
+const SEARCH_DEPTH_WEIGHT : usize = 20;
+
+fn get_tier(score : usize) -> DifficultyTier {
+    match score {
+        0..=39 => DifficultyTier::Easy,
+        40..=99 => DifficultyTier::Medium,
+        100..=199 => DifficultyTier::Hard,
+        _ => DifficultyTier::Expert,
+    }
+}
+
+impl Difficulty {
+    fn new(iteration_count : usize, hardest_reason : usize, search_node_count : usize, search_depth : usize) -> Self {
+        let search_needed = search_node_count > 0;
+        let mut score = iteration_count * ITERATION_WEIGHT + hardest_reason * REASON_WEIGHT;
+        if search_needed {
+            score += SEARCH_WEIGHT + search_node_count * SEARCH_NODE_WEIGHT + search_depth * SEARCH_DEPTH_WEIGHT;
+        }
+        Self {
+            score,
+            tier: get_tier(score),
+            iteration_count,
+            overlap_only: hardest_reason <= StepReason::Overlap(0).difficulty(),
+            search_needed,
+            search_node_count,
+            search_depth,
+        }
+    }
+}
+
+//None when the picross has no solution
+pub fn rate_picross_difficulty(picross : & PicrossBoard) -> Option<Difficulty> {
+    let mut row_col_picross = RowColPicross::new(picross);
+    row_col_picross.line_solve_method = LineSolveMethod::Dynamic;
+    row_col_picross.trace = Some(SolveTrace::new());
+    let solve = row_col_picross.solve();
+
+    let hardest_reason = row_col_picross.trace.take().unwrap().steps.iter()
+        .map(|step| step.reason.difficulty())
+        .max()
+        .unwrap_or(0);
+    let iteration_count = row_col_picross.iteration_count;
+
+    match solve {
+        Solve::Full => Some(Difficulty::new(iteration_count, hardest_reason, 0, 0)),
+        Solve::Partial => {
+            let mut solver = BacktrackSolver::new(row_col_picross);
+            match solver.solve() {
+                Solve::Full => Some(Difficulty::new(iteration_count, hardest_reason, solver.node_count, solver.max_depth)),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_difficulty_overlap_only() {
+        let picross = PicrossBoard::picross_from_clue_string("T0,2L1,1").unwrap();
+        let difficulty = rate_picross_difficulty(&picross).unwrap();
+        assert!(!difficulty.search_needed);
+        assert!(difficulty.overlap_only);
+    }
+
+    #[test]
+    fn test_difficulty_line_logic_only() {
+        //The ends of the crossing rows and columns are only OFF once their block is complete
+        let picross = PicrossBoard::picross_from_clue_string("T1,3,1L1,3,1").unwrap();
+        let difficulty = rate_picross_difficulty(&picross).unwrap();
+        assert!(!difficulty.search_needed);
+        assert!(!difficulty.overlap_only);
+
+        let mut row_col_picross = RowColPicross::new(&picross);
+        row_col_picross.trace = Some(SolveTrace::new());
+        assert!(row_col_picross.solve() == Solve::Full);
+        let reasons : Vec<usize> = row_col_picross.trace.unwrap().steps.iter().map(|step| step.reason.difficulty()).collect();
+        assert_eq!(reasons.iter().max(), Some(&StepReason::BlockComplete(0).difficulty()));
+        assert!(difficulty.tier == DifficultyTier::Easy);
+        assert!(difficulty.iteration_count >= 1);
+    }
+
+    #[test]
+    fn test_difficulty_with_search() {
+        let picross = PicrossBoard::picross_from_clue_string("T1,1L1,1").unwrap();
+        let difficulty = rate_picross_difficulty(&picross).unwrap();
+        assert!(difficulty.search_needed);
+        assert!(difficulty.search_depth >= 1);
+        assert!(difficulty.tier >= DifficultyTier::Hard);
+    }
+
+    #[test]
+    fn test_difficulty_order() {
+        let easy = rate_picross_difficulty(&PicrossBoard::picross_from_clue_string(CLUE_STRING_5X5).unwrap()).unwrap();
+        let hard = rate_picross_difficulty(&PicrossBoard::picross_from_clue_string(CLUE_STRING_30X30).unwrap()).unwrap();
+        assert!(easy.score < hard.score);
+        assert!(rate_picross_difficulty(&PicrossBoard::picross_from_clue_string("T2,2L1,1").unwrap()).is_none());
+    }
+}
@@ -0,0 +1,262 @@
+use super::*;
+
+//Blocks of size 0 are only used to describe an empty line
+fn get_blocks(clues : & ClueLine) -> Vec<usize> {
+    clues.iter().copied().filter(|&c| c > 0).collect()
+}
+
+pub struct LinePlacements {
+    line: PackedLine,
+    blocks: Vec<usize>,
+    //forward[j][i] : the first i cells can hold exactly the first j blocks
+    forward: Vec<Vec<bool>>,
+    //backward[j][i] : the cells from i to the end can hold exactly the blocks from j
+    backward: Vec<Vec<bool>>,
+    //off_count[i] : number of cells known as OFF in the first i cells
+    off_count: Vec<usize>,
+}
+
+impl LinePlacements {
+    pub fn new(clues : & ClueLine, values : & [CaseState]) -> Self {
+        Self::new_packed(clues, PackedLine::from_values(values))
+    }
+
+    pub fn new_packed(clues : & ClueLine, line : PackedLine) -> Self {
+        let blocks = get_blocks(clues);
+        let size = line.size();
+        let block_count = blocks.len();
+
+        let mut off_count = vec![0; size + 1];
+        for i in 0..size {
+            off_count[i + 1] = off_count[i] + (line.get(i) == CaseState::OFF) as usize;
+        }
+
+        let mut placements = Self {
+            line,
+            blocks,
+            forward: vec![vec![false; size + 1]; block_count + 1],
+            backward: vec![vec![false; size + 1]; block_count + 1],
+            off_count,
+        };
+
+        placements.forward[0][0] = true;
+        for i in 1..=size {
+            for j in 0..=block_count {
+                let mut possible = placements.can_be_off(i - 1) && placements.forward[j][i - 1];
+                if !possible && j > 0 {
+                    let block = placements.blocks[j - 1];
+                    possible = block <= i && placements.fits_from_left(j - 1, i - block);
+                }
+                placements.forward[j][i] = possible;
+            }
+        }
+
+        placements.backward[block_count][size] = true;
+        for i in (0..size).rev() {
+            for j in 0..=block_count {
+                let mut possible = placements.can_be_off(i) && placements.backward[j][i + 1];
+                if !possible && j < block_count {
+                    possible = placements.fits_from_right(j, i);
+                }
+                placements.backward[j][i] = possible;
+            }
+        }
+
+        placements
+    }
+
+    fn can_be_off(&self, idx : usize) -> bool {
+        self.line.get(idx) != CaseState::ON
+    }
+
+    fn can_hold_block(&self, start : usize, size : usize) -> bool {
+        match start.checked_add(size) {
+            Some(end) if end <= self.line.size() => self.off_count[end] == self.off_count[start],
+            _ => false,
+        }
+    }
+
+    //Block j can start at start, with the previous blocks packed on its left
+    fn fits_from_left(&self, j : usize, start : usize) -> bool {
+        if !self.can_hold_block(start, self.blocks[j]) {
+            return false;
+        }
+        match start {
+            0 => self.forward[j][0],
+            _ => self.can_be_off(start - 1) && self.forward[j][start - 1],
+        }
+    }
+
+    //Block j can start at start, with the next blocks packed on its right
+    fn fits_from_right(&self, j : usize, start : usize) -> bool {
+        if !self.can_hold_block(start, self.blocks[j]) {
+            return false;
+        }
+        let end = start + self.blocks[j];
+        if end == self.line.size() {
+            self.backward[j + 1][end]
+        } else {
+            self.can_be_off(end) && self.backward[j + 1][end + 1]
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.forward[self.blocks.len()][self.line.size()]
+    }
+
+    //Block j can be placed at start in at least one valid placement of the whole line
+    pub fn can_start_at(&self, j : usize, start : usize) -> bool {
+        self.fits_from_left(j, start) && self.fits_from_right(j, start)
+    }
+
+    //Intersection of all the valid placements, UNKNOWN where they disagree
+    pub fn get_common_line(&self) -> PackedLine {
+        let size = self.line.size();
+        let mut on_coverage : Vec<isize> = vec![0; size + 1];
+        for j in 0..self.blocks.len() {
+            let block = self.blocks[j];
+            if block > size {
+                continue;
+            }
+            for start in 0..=(size - block) {
+                if self.can_start_at(j, start) {
+                    on_coverage[start] += 1;
+                    on_coverage[start + block] -= 1;
+                }
+            }
+        }
+
+        let mut common = PackedLine::new(size);
+        let mut covering = 0;
+        for (i, coverage) in on_coverage[..size].iter().enumerate() {
+            covering += coverage;
+            let can_be_on = covering > 0;
+            let can_be_off = self.can_be_off(i) && (0..=self.blocks.len()).any(|j| self.forward[j][i] && self.backward[j][i + 1]);
+            match (can_be_on, can_be_off) {
+                (true, false) => common.set(i, CaseState::ON),
+                (false, true) => common.set(i, CaseState::OFF),
+                _ => {},
+            }
+        }
+        common
+    }
+
+    pub fn get_common_values(&self) -> Vec<CaseState> {
+        self.get_common_line().to_values()
+    }
+}
+
+//Invalid lines are left untouched
+pub fn solve_packed_line_dynamic(clues : & ClueLine, line : &mut PackedLine) -> Solve {
+    let placements = LinePlacements::new_packed(clues, line.clone());
+    if !placements.is_valid() {
+        return Solve::Contradiction;
+    }
+    *line = placements.get_common_line();
+    if line.is_full() {
+        Solve::Full
+    } else {
+        Solve::Partial
+    }
+}
+
+pub fn solve_line_dynamic(clues : & ClueLine, values : &mut [CaseState]) -> Solve {
+    let mut line = PackedLine::from_values(values);
+    let solve = solve_packed_line_dynamic(clues, &mut line);
+    if solve != Solve::Contradiction {
+        values.copy_from_slice(&line.to_values());
+    }
+    solve
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::image::CaseState::*;
+
+    #[test]
+    fn test_dynamic_empty_line() {
+        let mut values = vec![UNKNOWN; 3];
+        assert!(solve_line_dynamic(&vec![0], &mut values) == Solve::Full);
+        assert_eq!(values, vec![OFF, OFF, OFF]);
+
+        let mut values = vec![UNKNOWN; 3];
+        assert!(solve_line_dynamic(&vec![], &mut values) == Solve::Full);
+        assert_eq!(values, vec![OFF, OFF, OFF]);
+    }
+
+    #[test]
+    fn test_dynamic_overlap() {
+        let mut values = vec![UNKNOWN; 9];
+        assert!(solve_line_dynamic(&vec![2,2,2], &mut values) == Solve::Partial);
+        assert_eq!(values, vec![UNKNOWN, ON, UNKNOWN, UNKNOWN, ON, UNKNOWN, UNKNOWN, ON, UNKNOWN]);
+
+        let mut values = vec![UNKNOWN; 5];
+        assert!(solve_line_dynamic(&vec![1,3], &mut values) == Solve::Full);
+        assert_eq!(values, vec![ON, OFF, ON, ON, ON]);
+    }
+
+    #[test]
+    fn test_dynamic_with_known_cells() {
+        let mut values = vec![UNKNOWN, UNKNOWN, UNKNOWN, UNKNOWN, UNKNOWN, UNKNOWN, UNKNOWN, OFF, UNKNOWN, UNKNOWN];
+        assert!(solve_line_dynamic(&vec![6,1], &mut values) == Solve::Partial);
+        assert_eq!(values, vec![UNKNOWN, ON, ON, ON, ON, ON, UNKNOWN, OFF, UNKNOWN, UNKNOWN]);
+
+        let mut values = vec![UNKNOWN, ON, UNKNOWN, UNKNOWN, UNKNOWN];
+        assert!(solve_line_dynamic(&vec![2], &mut values) == Solve::Partial);
+        assert_eq!(values, vec![UNKNOWN, ON, UNKNOWN, OFF, OFF]);
+    }
+
+    #[test]
+    fn test_dynamic_invalid_line() {
+        let mut values = vec![UNKNOWN; 4];
+        assert!(solve_line_dynamic(&vec![2,2], &mut values) == Solve::Contradiction);
+        assert_eq!(values, vec![UNKNOWN; 4]);
+
+        let mut values = vec![ON, ON, UNKNOWN, UNKNOWN];
+        assert!(solve_line_dynamic(&vec![1], &mut values) == Solve::Contradiction);
+
+        let mut values = vec![UNKNOWN; 4];
+        assert!(solve_line_dynamic(&vec![usize::MAX, 1], &mut values) == Solve::Contradiction);
+    }
+
+    #[test]
+    fn test_dynamic_matches_brut_force() {
+        let cases : Vec<(ClueLine, Vec<CaseState>)> = vec![
+            (vec![1,1,2,1,1], vec![UNKNOWN; 10]),
+            (vec![3,1], vec![UNKNOWN, UNKNOWN, ON, UNKNOWN, UNKNOWN, UNKNOWN, OFF, UNKNOWN]),
+            (vec![1,2], vec![UNKNOWN, UNKNOWN, UNKNOWN, UNKNOWN, ON, UNKNOWN, UNKNOWN]),
+            (vec![4], vec![UNKNOWN, UNKNOWN, UNKNOWN, UNKNOWN, UNKNOWN, ON, UNKNOWN, UNKNOWN]),
+        ];
+        for (clues, values) in cases {
+            let mut brut_force = PicrossLineUnit::new(values.len(), clues.to_vec());
+            brut_force.values = values.to_vec();
+            let brut_force_solve = LineUnitSolver::new(&mut brut_force).solve();
+
+            let mut dynamic = values.to_vec();
+            assert!(solve_line_dynamic(&clues, &mut dynamic) == brut_force_solve);
+            assert_eq!(dynamic, brut_force.values);
+        }
+    }
+
+    #[test]
+    fn test_dynamic_packed_line() {
+        let mut line = PackedLine::new(100);
+        line.set(50, ON);
+        assert!(solve_packed_line_dynamic(&vec![60], &mut line) == Solve::Partial);
+        assert_eq!(line.to_values()[40..60].to_vec(), vec![ON; 20]);
+        assert!(line.get(39) == UNKNOWN);
+        assert!(line.get(60) == UNKNOWN);
+
+        line.set(99, OFF);
+        line.set(40, UNKNOWN);
+        assert!(solve_packed_line_dynamic(&vec![60], &mut line) == Solve::Partial);
+        assert!(line.get(40) == ON);
+        assert!(line.get(98) == UNKNOWN);
+
+        let mut line = PackedLine::from_values(&[ON, ON, UNKNOWN]);
+        assert!(solve_packed_line_dynamic(&vec![1,1], &mut line) == Solve::Contradiction);
+        assert_eq!(line.to_values(), vec![ON, ON, UNKNOWN]);
+    }
+}
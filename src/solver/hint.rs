@@ -0,0 +1,101 @@
+use super::*;
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum Hint {
+    Deduction(SolveStep),
+    //The player's cells in this line cannot match its clues
+    Mistake(Orientation, usize),
+    Solved,
+    //Single lines are not enough, the player has to combine several lines or guess
+    NoDeduction,
+}
+
+fn get_line_unit(picross : & PicrossBoard, orientation : Orientation, idx : usize) -> PicrossLineUnit {
+    let (clues, line) = match orientation {
+        Orientation::Horizontal => (picross.left.get_line_copy(idx), picross.image.get_row(idx)),
+        Orientation::Vertical => (picross.top.get_line_copy(idx), picross.image.get_col(idx)),
+    };
+    let mut line_unit = PicrossLineUnit::new(line.size(), clues);
+    for i in 0..line.size() {
+        line_unit.set_value(i, line.get(i));
+    }
+    line_unit
+}
+
+//Only looks at the lines one by one, from the player's board as it is
+pub fn get_hint(picross : & PicrossBoard) -> Hint {
+    let lines = (0..picross.height()).map(|i| (Orientation::Horizontal, i))
+        .chain((0..picross.width()).map(|i| (Orientation::Vertical, i)));
+
+    let mut trace = SolveTrace::new();
+    let mut unknown_left = false;
+    for (orientation, idx) in lines {
+        let mut line_unit = get_line_unit(picross, orientation, idx);
+        let before = line_unit.values.clone();
+        unknown_left |= before.contains(&CaseState::UNKNOWN);
+
+        let mut solver = LineUnitSolver::new(&mut line_unit);
+        solver.solve_method = LineSolveMethod::Dynamic;
+        if solver.solve() == Solve::Contradiction {
+            return Hint::Mistake(orientation, idx);
+        }
+        trace.record_line(orientation, idx, &line_unit.clues, &before, &line_unit.values);
+    }
+
+    //Easiest reason first, then the step revealing the most cells
+    let best = trace.steps.into_iter()
+        .min_by_key(|step| (step.reason.difficulty(), std::cmp::Reverse(step.changes.len())));
+    match (best, unknown_left) {
+        (Some(step), _) => Hint::Deduction(step),
+        (None, false) => Hint::Solved,
+        (None, true) => Hint::NoDeduction,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::image::CaseState::*;
+
+    #[test]
+    fn test_hint_on_empty_board() {
+        let picross = PicrossBoard::picross_from_clue_string("T1,3,1L1,3,1").unwrap();
+        match get_hint(&picross) {
+            Hint::Deduction(step) => {
+                assert!(step.orientation == Orientation::Horizontal);
+                assert_eq!(step.index, 1);
+                assert_eq!(step.reason, StepReason::Overlap(0));
+                assert_eq!(step.changes.len(), 3);
+            }
+            _ => panic!("A deduction was expected"),
+        }
+    }
+
+    #[test]
+    fn test_hint_follows_player_progress() {
+        let mut picross = PicrossBoard::picross_from_clue_string("T2,1L1,2").unwrap();
+        picross.image.set_value(0, 1, ON);
+        picross.image.set_value(1, 1, ON);
+        picross.image.set_value(0, 0, ON);
+        match get_hint(&picross) {
+            Hint::Deduction(step) => {
+                assert_eq!(step.changes, vec![CellChange { x: 1, y: 0, value: OFF }]);
+            }
+            _ => panic!("A deduction was expected"),
+        }
+
+        picross.image.set_value(1, 0, OFF);
+        assert!(get_hint(&picross) == Hint::Solved);
+    }
+
+    #[test]
+    fn test_hint_mistake_and_no_deduction() {
+        let mut picross = PicrossBoard::picross_from_clue_string("T1,1L1,1").unwrap();
+        assert!(get_hint(&picross) == Hint::NoDeduction);
+
+        picross.image.set_value(0, 0, ON);
+        picross.image.set_value(1, 0, ON);
+        assert!(get_hint(&picross) == Hint::Mistake(Orientation::Horizontal, 0));
+    }
+}
@@ -1,34 +1,65 @@
 use super::*;
 
-pub fn invalidate_line(clues : & ClueLine, line : & ImageLine) -> bool {
+//Every run of ON cells must fit in one of the clues its cells can belong to
+fn invalidate_runs_with_range(clues : & ClueLine, line : & ImageLine) -> bool {
     let size = line.size();
     let range = get_clue_index_range(clues, size);
-    println!("range : {:?}, size {}", range, size);
-    return false;
+    let mut run_start = 0;
+    for i in 0..=size {
+        if i < size && line.get(i) == CaseState::ON {
+            continue;
+        }
+        if i > run_start {
+            let run_size = i - run_start;
+            let first_clue = range[run_start].0;
+            let last_clue = range[i - 1].1;
+            if !(first_clue..=last_clue).any(|j| j < clues.len() && clues[j] >= run_size) {
+                return true;
+            }
+        }
+        run_start = i + 1;
+    }
+    false
+}
+
+pub fn invalidate_line(clues : & ClueLine, line : & ImageLine) -> bool {
+    let size = line.size();
+    //Blocks of size 0 only describe an empty line
+    let blocks : ClueLine = clues.iter().copied().filter(|&c| c > 0).collect();
+    match get_min_line_size(&blocks) {
+        Some(occupied) if occupied <= size => {},
+        _ => return true,
+    }
+
+    //Quick rejection first, then the exact check on all the placements left
+    if invalidate_runs_with_range(&blocks, line) {
+        return true;
+    }
+    !LinePlacements::new_packed(&blocks, PackedLine::from_image_line(line)).is_valid()
 }
 
-pub fn invalidate_row(picross : & mut PicrossBoard, idx : usize) -> bool{
-    invalidate_line(&picross.left.get_line(idx), &picross.image.get_row(idx))
+pub fn invalidate_row(picross : & PicrossBoard, idx : usize) -> bool{
+    invalidate_line(picross.left.get_line(idx), &picross.image.get_row(idx))
 }
 
-pub fn invalidate_col(picross : & mut PicrossBoard, idx : usize) -> bool{
-    invalidate_line(&picross.top.get_line(idx), &picross.image.get_col(idx))
+pub fn invalidate_col(picross : & PicrossBoard, idx : usize) -> bool{
+    invalidate_line(picross.top.get_line(idx), &picross.image.get_col(idx))
 }
 
-pub fn invalidate_picross(picross : & mut PicrossBoard) -> bool {
-    let height = picross.height();
-    let width = picross.width();
-    for i in 0..height {
+//Rows are reported as Horizontal lines and columns as Vertical ones
+pub fn invalidate_picross(picross : & PicrossBoard) -> Vec<(Orientation, usize)> {
+    let mut invalid_lines = Vec::new();
+    for i in 0..picross.height() {
         if invalidate_row(picross, i) {
-            return true;
+            invalid_lines.push((Orientation::Horizontal, i));
         }
     }
-    for i in 0..width {
+    for i in 0..picross.width() {
         if invalidate_col(picross, i) {
-            return true;
+            invalid_lines.push((Orientation::Vertical, i));
         }
     }
-    false
+    invalid_lines
 }
 
 #[cfg(test)]
@@ -43,4 +74,56 @@ mod tests {
         let clues = vec![1,1];
         assert!(!invalidate_line(&clues, &img.get_row(0)));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_partial_line_invalidation() {
+        let clues = vec![2,1];
+        let img = create_one_line_board(&[UNKNOWN, ON, UNKNOWN, UNKNOWN, UNKNOWN]);
+        assert!(!invalidate_line(&clues, &img.get_row(0)));
+
+        let img = create_one_line_board(&[UNKNOWN, ON, ON, ON, UNKNOWN]);
+        assert!(invalidate_line(&clues, &img.get_row(0)));
+
+        let img = create_one_line_board(&[UNKNOWN, OFF, UNKNOWN, OFF, UNKNOWN]);
+        assert!(invalidate_line(&clues, &img.get_row(0)));
+
+        let img = create_one_line_board(&[ON, UNKNOWN, ON, UNKNOWN, ON]);
+        assert!(invalidate_line(&clues, &img.get_row(0)));
+
+        let img = create_one_line_board(&[UNKNOWN, UNKNOWN, UNKNOWN, UNKNOWN]);
+        assert!(!invalidate_line(&clues, &img.get_row(0)));
+
+        let img = create_one_line_board(&[UNKNOWN, UNKNOWN, UNKNOWN]);
+        assert!(invalidate_line(&clues, &img.get_row(0)));
+    }
+
+    #[test]
+    fn test_empty_line_invalidation() {
+        let img = create_one_line_board(&[UNKNOWN, OFF, UNKNOWN]);
+        assert!(!invalidate_line(&vec![0], &img.get_row(0)));
+        assert!(!invalidate_line(&vec![], &img.get_row(0)));
+
+        let img = create_one_line_board(&[UNKNOWN, ON, UNKNOWN]);
+        assert!(invalidate_line(&vec![0], &img.get_row(0)));
+        assert!(invalidate_line(&vec![], &img.get_row(0)));
+
+        let img = create_one_line_board(&[UNKNOWN, ON, ON]);
+        assert!(!invalidate_line(&vec![0,2], &img.get_row(0)));
+    }
+
+    #[test]
+    fn test_picross_invalidation() {
+        let mut picross = PicrossBoard::picross_from_clue_string("T1,1,2L11,2").unwrap();
+        assert!(invalidate_picross(&picross).is_empty());
+
+        picross.image.set_value(0, 0, ON);
+        picross.image.set_value(1, 1, ON);
+        assert!(invalidate_picross(&picross).is_empty());
+
+        picross.image.set_value(0, 1, ON);
+        assert_eq!(invalidate_picross(&picross), vec![(Orientation::Vertical, 0)]);
+
+        picross.image.set_value(2, 1, ON);
+        assert_eq!(invalidate_picross(&picross), vec![(Orientation::Horizontal, 1), (Orientation::Vertical, 0)]);
+    }
+}
@@ -0,0 +1,133 @@
+use super::*;
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+type LineCacheKey = (ClueLine, PackedLine);
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct LineCacheStats {
+    pub hits: usize,
+    pub misses: usize,
+    pub size: usize,
+}
+
+struct LineCacheEntries {
+    solutions: HashMap<LineCacheKey, (Solve, PackedLine)>,
+    //Insertion order, the oldest entries are dropped first once the cache is full
+    order: VecDeque<LineCacheKey>,
+}
+
+//Every line solve method gives the same result for a line, so the cache can be shared between methods,
+//picross and threads
+pub struct LineCache {
+    capacity: usize,
+    entries: Mutex<LineCacheEntries>,
+    hits: AtomicUsize,
+    misses: AtomicUsize,
+}
+
+impl LineCache {
+    pub fn new(capacity : usize) -> Self {
+        Self {
+            capacity,
+            entries: Mutex::new(LineCacheEntries {
+                solutions: HashMap::new(),
+                order: VecDeque::new(),
+            }),
+            hits: AtomicUsize::new(0),
+            misses: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn capacity(&self) -> usize { self.capacity }
+
+    pub fn get(&self, clues : & ClueLine, line : & PackedLine) -> Option<(Solve, PackedLine)> {
+        let entries = self.entries.lock().unwrap();
+        match entries.solutions.get(&(clues.to_vec(), line.clone())) {
+            Some(solution) => {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                Some(solution.clone())
+            }
+            None => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+        }
+    }
+
+    pub fn insert(&self, clues : & ClueLine, line : & PackedLine, solve : Solve, solution : & PackedLine) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut entries = self.entries.lock().unwrap();
+        let key = (clues.to_vec(), line.clone());
+        if entries.solutions.contains_key(&key) {
+            return;
+        }
+        while entries.solutions.len() >= self.capacity {
+            match entries.order.pop_front() {
+                Some(oldest) => { entries.solutions.remove(&oldest); }
+                None => break,
+            }
+        }
+        entries.order.push_back(key.clone());
+        entries.solutions.insert(key, (solve, solution.clone()));
+    }
+
+    pub fn stats(&self) -> LineCacheStats {
+        LineCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            size: self.entries.lock().unwrap().solutions.len(),
+        }
+    }
+
+    pub fn clear(&self) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.solutions.clear();
+        entries.order.clear();
+        self.hits.store(0, Ordering::Relaxed);
+        self.misses.store(0, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::image::CaseState::*;
+
+    #[test]
+    fn test_line_cache_hit_and_miss() {
+        let cache = LineCache::new(10);
+        let line = PackedLine::from_values(&[UNKNOWN, ON, UNKNOWN]);
+        assert!(cache.get(&vec![2], &line).is_none());
+
+        let solution = PackedLine::from_values(&[UNKNOWN, ON, UNKNOWN]);
+        cache.insert(&vec![2], &line, Solve::Partial, &solution);
+        assert!(cache.get(&vec![2], &line) == Some((Solve::Partial, solution)));
+        assert!(cache.get(&vec![1], &line).is_none());
+        assert_eq!(cache.stats(), LineCacheStats { hits: 1, misses: 2, size: 1 });
+
+        cache.clear();
+        assert_eq!(cache.stats(), LineCacheStats { hits: 0, misses: 0, size: 0 });
+    }
+
+    #[test]
+    fn test_line_cache_bound() {
+        let cache = LineCache::new(2);
+        let lines : Vec<PackedLine> = (1..=3).map(PackedLine::new).collect();
+        for line in lines.iter() {
+            cache.insert(&vec![1], line, Solve::Partial, line);
+        }
+        assert_eq!(cache.stats().size, 2);
+        assert!(cache.get(&vec![1], &lines[0]).is_none());
+        assert!(cache.get(&vec![1], &lines[2]).is_some());
+
+        let cache = LineCache::new(0);
+        cache.insert(&vec![1], &lines[0], Solve::Partial, &lines[0]);
+        assert_eq!(cache.stats().size, 0);
+    }
+}
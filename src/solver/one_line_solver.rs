@@ -12,57 +12,11 @@ pub enum Verbose {
 pub enum LineSolveMethod {
     BrutForce,
     SpaceDistribution,
+    Dynamic,
 }
 
 pub fn validate_line_unit(line : &PicrossLineUnit) -> bool {
-    let nb_clues = line.clues.len();
-    let mut current_clue : usize = 0;
-    let mut current_block_size : usize = 0;
-    for i in 0..line.values.len() {
-        match line.values[i] {
-            CaseState::ON => { //Start or continuing a block
-                //Check if a clue remains
-                if current_clue == nb_clues {
-                    return false;
-                }
-                
-                //Then increase block size
-                current_block_size += 1;
-
-                //Check if block is already too big
-                if current_block_size > line.clues[current_clue] {
-                    return false;
-                }
-            },
-            _ => {
-                if current_block_size > 0 {
-
-                    //Check if terminated block has good size
-                    if current_block_size != line.clues[current_clue] {
-                        return false;
-                    }
-
-                    //Go to next clue and reset block size
-                    current_clue += 1;
-                    current_block_size = 0;
-                }
-            }
-        }
-    }
-    //Finish last block
-    if current_block_size > 0 {
-        if current_block_size != line.clues[current_clue] {
-            return false;
-        }
-        current_clue += 1;
-    }
-
-    //Check if we have validated all clues
-    if (current_clue != nb_clues) && (line.clues[current_clue] != 0) {
-        return false;
-    }
-
-    true
+    validate_packed_line(&line.clues, &PackedLine::from_values(&line.values))
 }
 
 pub struct LineUnitSolver<'a> {
@@ -73,6 +27,14 @@ pub struct LineUnitSolver<'a> {
     pub verbose : Verbose,
     pub solve_method : LineSolveMethod,
     pub spaces : Vec<usize>,
+    //Cells known before solving, every try has to be compatible with them
+    pub known : PackedLine,
+    //The line being tried, validated and intersected word by word
+    pub current : PackedLine,
+    //Consulted before enumerating, solved lines are added to it
+    pub cache : Option<&'a LineCache>,
+    pub budget : Option<&'a SolveBudget>,
+    pub exhausted : bool,
 }
 
 impl<'a> LineUnitSolver<'a>{
@@ -89,6 +51,7 @@ impl<'a> LineUnitSolver<'a>{
 
         let space_slot_count = picross.clues.len() + 1;
         let spaces : Vec<usize> = vec![0; space_slot_count];
+        let known = PackedLine::from_values(&picross.values);
 
         Self {
             picross,
@@ -98,6 +61,11 @@ impl<'a> LineUnitSolver<'a>{
             verbose : Verbose::Quiet,
             solve_method : LineSolveMethod::BrutForce,
             spaces,
+            current : known.clone(),
+            known,
+            cache : None,
+            budget : None,
+            exhausted : false,
         }
     }
 
@@ -105,21 +73,37 @@ impl<'a> LineUnitSolver<'a>{
         if self.solve_method == LineSolveMethod::SpaceDistribution {
             self.spaces.fill(0);
             let last_index = self.spaces.len() - 1;
-            self.spaces[last_index] = self.picross.free_space_count();
+            self.spaces[last_index] = self.picross.free_space_count().unwrap_or(0);
         }
         self.count = 0;
 
         for i in 0..self.editable.len() {
             self.picross.values[self.editable[i]] = CaseState::OFF;
         }
-        
+        self.current = PackedLine::from_values(&self.picross.values);
+    }
+
+    //Only the cells, the tries stay counted
+    fn restore_line(&mut self) {
+        for i in 0..self.editable.len() {
+            self.picross.values[self.editable[i]] = CaseState::UNKNOWN;
+        }
+    }
+
+    fn is_over_budget(&mut self) -> bool {
+        if let Some(budget) = self.budget {
+            if self.count.is_multiple_of(BUDGET_CHECK_PERIOD) && budget.is_exhausted(self.count) {
+                self.exhausted = true;
+            }
+        }
+        self.exhausted
     }
 
     pub fn reset_solve(&mut self) {
         if self.solve_method == LineSolveMethod::SpaceDistribution {
             self.spaces.fill(0);
             let last_index = self.spaces.len() - 1;
-            self.spaces[last_index] = self.picross.free_space_count();
+            self.spaces[last_index] = self.picross.free_space_count().unwrap_or(0);
         }
         self.count = 0;
 
@@ -130,13 +114,18 @@ impl<'a> LineUnitSolver<'a>{
 
     pub fn next_try(&mut self) {
         for i in (0..self.editable.len()).rev() {
-            match self.picross.values[self.editable[i]] {
+            let idx = self.editable[i];
+            match self.picross.values[idx] {
                 CaseState::OFF => {
-                    self.picross.values[self.editable[i]] = CaseState::ON;
+                    self.picross.values[idx] = CaseState::ON;
+                    self.current.set(idx, CaseState::ON);
                     self.count += 1;
                     return
                 }
-                _ => self.picross.values[self.editable[i]] = CaseState::OFF,
+                _ => {
+                    self.picross.values[idx] = CaseState::OFF;
+                    self.current.set(idx, CaseState::OFF);
+                }
             }
         }
         panic!("One line solver has overflown. Program stops to prevent infinite loop.");
@@ -170,108 +159,169 @@ impl<'a> LineUnitSolver<'a>{
     }
 
     pub fn construct_line_from_spaces(& self) -> Vec<CaseState> {
-        let mut line = vec![CaseState::OFF; self.picross.values.len()];
+        self.construct_packed_line_from_spaces().to_values()
+    }
+
+    pub fn construct_packed_line_from_spaces(& self) -> PackedLine {
+        let size = self.picross.values.len();
+        let mut line = PackedLine::new(size);
+        line.fill(0, size, CaseState::OFF);
         let mut cursor = self.spaces[0];
         for i in 0..self.picross.clues.len() {
-            for _ in 0..self.picross.clues[i] {
-                line[cursor] = CaseState::ON;
-                cursor += 1;
-            }
-            cursor += self.spaces[i+1] + 1;
+            line.fill(cursor, cursor + self.picross.clues[i], CaseState::ON);
+            cursor += self.picross.clues[i] + self.spaces[i+1] + 1;
         }
-        return line;
+        line
     }
 
     pub fn next_space(&mut self) -> bool {
-        while self.spaces[0] != self.picross.free_space_count() {
-            
-            self.next_space_try();
+        while self.spaces[0] != self.picross.free_space_count().unwrap_or(0) {
+            if self.is_over_budget() {
+                return false;
+            }
 
-            let try_line = self.construct_line_from_spaces();
+            self.next_space_try();
 
-            if self.verbose == Verbose::Full { print!("try{:?}", self.spaces); display_line(&try_line); println!(""); }
+            let try_line = self.construct_packed_line_from_spaces();
 
-            let mut is_valid = true;
-            for i in 0..self.fixed.len() {
-                let idx = self.fixed[i];
-                if try_line[idx] != self.picross.values[idx] {
-                    is_valid = false;
-                    break;
-                }
-            }
+            if self.verbose == Verbose::Full { print!("try{:?}", self.spaces); display_line(&try_line.to_values()); println!(); }
 
-            if is_valid {
+            if self.known.is_compatible(&try_line) {
                 for i in 0..self.editable.len() {
                     let idx = self.editable[i];
-                    self.picross.values[idx] = try_line[idx];
+                    self.picross.values[idx] = try_line.get(idx);
                 }
+                self.current = try_line;
                 return true;
             }
         }
-        return false;
+        false
     }
 
+    #[allow(clippy::should_implement_trait)]
     pub fn next(&mut self) -> bool {
         if self.solve_method == LineSolveMethod::SpaceDistribution {
-            return self.next_space();
+            self.next_space()
         } else {
             while self.count != self.max_count() - 1 {
+                if self.is_over_budget() {
+                    return false;
+                }
                 self.next_try();
     
                 if self.verbose == Verbose::Full { print!("try   "); self.picross.display(); }
     
-                if validate_line_unit(self.picross) {
+                if validate_packed_line(&self.picross.clues, &self.current) {
                     return true
                 }
             }
-            return false
+            false
         }
     }
 
     pub fn max_count(&mut self) -> usize {
-        return 2_usize.pow(self.editable.len() as u32);
+        2_usize.pow(self.editable.len() as u32)
     }
 }
 
 impl<'a> Solvable for LineUnitSolver<'a> {
     fn solve(&mut self) -> Solve {
-        let mut solve_res = Solve::None;
-
-        if self.solve_method == LineSolveMethod::SpaceDistribution && self.picross.free_space_count() == 0 {
-            self.picross.values = self.construct_line_from_spaces();
-            return Solve::Full;
+        if let Some(budget) = self.budget {
+            if budget.is_exhausted(0) {
+                self.exhausted = true;
+                return Solve::Exhausted;
+            }
         }
 
-        if self.verbose != Verbose::Quiet { print!("init  "); self.picross.display(); println!(""); }
+        let cache = match self.cache {
+            Some(cache) => cache,
+            None => return self.solve_counted(),
+        };
 
-        self.init_solve();
-        if validate_line_unit(self.picross) {
-            solve_res = Solve::Full;    
+        let line = PackedLine::from_values(&self.picross.values);
+        if let Some((solve, solution)) = cache.get(&self.picross.clues, &line) {
+            if self.verbose != Verbose::Quiet { print!("cache "); display_line(&solution.to_values()); println!(); }
+            self.picross.values = solution.to_values();
+            return solve;
         }
-        let mut common = self.picross.values.to_vec();
+        let solve = self.solve_counted();
+        if solve != Solve::Exhausted {
+            cache.insert(&self.picross.clues, &line, solve, &PackedLine::from_values(&self.picross.values));
+        }
+        solve
+    }
+}
 
-        while self.next() {
+impl<'a> LineUnitSolver<'a> {
+    fn solve_counted(&mut self) -> Solve {
+        let solve = self.solve_uncached();
+        if let Some(budget) = self.budget {
+            budget.add_line_tries(self.count);
+        }
+        solve
+    }
 
-            if solve_res == Solve::None {
-                common = self.picross.values.to_vec();
-                solve_res = Solve::Full;
-            }
+    fn solve_uncached(&mut self) -> Solve {
+        if self.solve_method == LineSolveMethod::Dynamic {
+            if self.verbose != Verbose::Quiet { print!("init  "); self.picross.display(); println!(); }
+            self.count = 1;
+            let solve_res = solve_line_dynamic(&self.picross.clues, &mut self.picross.values);
+            if self.verbose != Verbose::Quiet { print!("final "); self.picross.display(); }
+            return solve_res;
+        }
 
-            for i in 0..self.editable.len() {
-                let idx = self.editable[i];
-                if self.picross.values[idx] != common[idx] {
-                    solve_res = Solve::Partial;
-                    common[idx] = CaseState::UNKNOWN;
+        if self.solve_method == LineSolveMethod::SpaceDistribution {
+            match self.picross.free_space_count() {
+                None => return Solve::Contradiction,
+                Some(0) => {
+                    let line = self.construct_packed_line_from_spaces();
+                    if !self.known.is_compatible(&line) {
+                        return Solve::Contradiction;
+                    }
+                    self.picross.values = line.to_values();
+                    return Solve::Full;
                 }
+                Some(_) => {}
+            }
+        }
+
+        if self.verbose != Verbose::Quiet { print!("init  "); self.picross.display(); println!(); }
+
+        //Every valid try is intersected with the previous ones
+        self.init_solve();
+        let mut common : Option<PackedLine> = None;
+        if validate_packed_line(&self.picross.clues, &self.current) {
+            common = Some(self.current.clone());
+        }
+
+        while self.next() {
+            match common.as_mut() {
+                Some(line) => line.intersect(&self.current),
+                None => common = Some(self.current.clone()),
             }
 
             if self.verbose != Verbose::Quiet { print!("valid "); self.picross.display(); }
 
         }
 
-        self.picross.values = common.to_vec();
-        if self.verbose != Verbose::Quiet {  println!(""); print!("final "); self.picross.display(); }
-        return solve_res;
+        if self.exhausted {
+            self.restore_line();
+            return Solve::Exhausted;
+        }
+
+        let common = match common {
+            Some(common) => common,
+            None => {
+                //No valid line, keep the line as it was before solving
+                self.restore_line();
+                return Solve::Contradiction;
+            }
+        };
+        let solve_res = if common.is_full() { Solve::Full } else { Solve::Partial };
+
+        self.picross.values = common.to_values();
+        if self.verbose != Verbose::Quiet {  println!(); print!("final "); self.picross.display(); }
+        solve_res
     }
 }
 
@@ -430,6 +480,28 @@ mod tests {
         assert!(solver.picross.values[8] == CaseState::UNKNOWN);
     }
 
+    #[test]
+    fn test_solve_contradiction() {
+        for &method in [LineSolveMethod::BrutForce, LineSolveMethod::SpaceDistribution, LineSolveMethod::Dynamic].iter() {
+            let clues: ClueLine = vec![1,2];
+            let size: usize = 5;
+            let mut picross = PicrossLineUnit::new(size, clues);
+            picross.values[0] = CaseState::ON;
+            picross.values[1] = CaseState::ON;
+            let mut solver = LineUnitSolver::new(& mut picross);
+            solver.solve_method = method;
+            assert!(solver.solve() == Solve::Contradiction);
+            assert!(solver.picross.values[2] == CaseState::UNKNOWN);
+
+            let clues: ClueLine = vec![2,2];
+            let size: usize = 4;
+            let mut picross = PicrossLineUnit::new(size, clues);
+            let mut solver = LineUnitSolver::new(& mut picross);
+            solver.solve_method = method;
+            assert!(solver.solve() == Solve::Contradiction);
+        }
+    }
+
     #[test]
     fn test_space_solve() {
         let clues: ClueLine = vec![1,1,1];
@@ -438,7 +510,7 @@ mod tests {
         let mut solver = LineUnitSolver::new(& mut picross);
         solver.verbose = Verbose::Full;
         solver.solve_method = LineSolveMethod::SpaceDistribution;
-        println!("");
+        println!();
         assert!(solver.solve() == Solve::Partial);
     }
 }
\ No newline at end of file
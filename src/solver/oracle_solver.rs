@@ -0,0 +1,172 @@
+use super::*;
+
+//A slow whole board solver for tests, independent from the line solvers : every valid placement of each row
+//is tried in turn, the columns only prune the rows stacked so far
+
+//Every arrangement of the blocks in a line of this size
+fn get_row_placements(blocks : & [usize], size : usize) -> Vec<Vec<bool>> {
+    let mut placements = Vec::new();
+    let mut row = vec![false; size];
+    place_blocks(blocks, 0, &mut row, &mut placements);
+    placements
+}
+
+fn place_blocks(blocks : & [usize], start : usize, row : &mut Vec<bool>, placements : &mut Vec<Vec<bool>>) {
+    let block = match blocks.first() {
+        Some(&block) => block,
+        None => {
+            placements.push(row.clone());
+            return;
+        }
+    };
+    let needed = match get_min_line_size(blocks) {
+        Some(needed) if needed <= row.len().saturating_sub(start) => needed,
+        _ => return,
+    };
+    for p in start..=(row.len() - needed) {
+        for cell in row[p..p + block].iter_mut() {
+            *cell = true;
+        }
+        place_blocks(&blocks[1..], p + block + 1, row, placements);
+        for cell in row[p..p + block].iter_mut() {
+            *cell = false;
+        }
+    }
+}
+
+//Progress of a column from the top : the block being filled and how many of its cells are ON
+#[derive(Clone, Copy)]
+struct ColumnState {
+    block: usize,
+    run: usize,
+}
+
+pub struct OracleSolver {
+    height: usize,
+    width: usize,
+    col_blocks: Vec<Vec<usize>>,
+    //Cells needed below the current block, including the gaps
+    col_remaining: Vec<Vec<usize>>,
+    row_placements: Vec<Vec<Vec<bool>>>,
+    pub limit: usize,
+    pub solutions: Vec<ImageBoard>,
+}
+
+impl OracleSolver {
+    pub fn new(picross : & PicrossBoard, limit : usize) -> Self {
+        let height = picross.height();
+        let width = picross.width();
+        let blocks_of = |clues : ClueLine| -> Vec<usize> { clues.into_iter().filter(|&c| c > 0).collect() };
+        let col_blocks : Vec<Vec<usize>> = (0..width).map(|x| blocks_of(picross.top.get_line_copy(x))).collect();
+        let col_remaining = col_blocks.iter()
+            .map(|blocks| (0..blocks.len()).map(|j| blocks[j + 1..].iter().fold(0_usize, |sum, b| sum.saturating_add(b.saturating_add(1)))).collect())
+            .collect();
+        let row_placements = (0..height)
+            .map(|y| get_row_placements(&blocks_of(picross.left.get_line_copy(y)), width))
+            .collect();
+        Self {
+            height,
+            width,
+            col_blocks,
+            col_remaining,
+            row_placements,
+            limit,
+            solutions: Vec::new(),
+        }
+    }
+
+    //None when the column cannot be completed with the rows left
+    fn next_column_state(&self, x : usize, state : ColumnState, on : bool, rows_left : usize) -> Option<ColumnState> {
+        let blocks = &self.col_blocks[x];
+        let mut next = state;
+        if on {
+            if next.block >= blocks.len() || next.run == blocks[next.block] {
+                return None;
+            }
+            next.run += 1;
+        } else if next.run > 0 {
+            if next.run != blocks[next.block] {
+                return None;
+            }
+            next.block += 1;
+            next.run = 0;
+        }
+
+        let needed = match blocks.get(next.block) {
+            //The gaps before the next blocks are counted in col_remaining
+            Some(&block) if next.run == block => self.col_remaining[x][next.block],
+            Some(&block) => (block - next.run).saturating_add(self.col_remaining[x][next.block]),
+            None => 0,
+        };
+        if needed > rows_left {
+            return None;
+        }
+        Some(next)
+    }
+
+    fn search(&mut self, y : usize, columns : & [ColumnState], rows : &mut Vec<usize>) {
+        if self.solutions.len() >= self.limit {
+            return;
+        }
+        if y == self.height {
+            let mut image = ImageBoard::new_empty(self.height, self.width);
+            for (row_y, &placement) in rows.iter().enumerate() {
+                for (x, &on) in self.row_placements[row_y][placement].iter().enumerate() {
+                    image.set_value(x, row_y, if on { CaseState::ON } else { CaseState::OFF });
+                }
+            }
+            self.solutions.push(image);
+            return;
+        }
+
+        let rows_left = self.height - y - 1;
+        for placement in 0..self.row_placements[y].len() {
+            let next : Option<Vec<ColumnState>> = (0..self.width)
+                .map(|x| self.next_column_state(x, columns[x], self.row_placements[y][placement][x], rows_left))
+                .collect();
+            if let Some(next) = next {
+                rows.push(placement);
+                self.search(y + 1, &next, rows);
+                rows.pop();
+            }
+        }
+    }
+
+    pub fn find_solutions(&mut self) -> &Vec<ImageBoard> {
+        self.solutions.clear();
+        let columns = vec![ColumnState { block: 0, run: 0 }; self.width];
+        self.search(0, &columns, &mut Vec::new());
+        &self.solutions
+    }
+}
+
+pub fn find_oracle_solutions(picross : & PicrossBoard, limit : usize) -> Vec<ImageBoard> {
+    let mut solver = OracleSolver::new(picross, limit);
+    solver.find_solutions();
+    solver.solutions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_row_placements() {
+        assert_eq!(get_row_placements(&[2, 1], 5).len(), 3);
+        assert_eq!(get_row_placements(&[], 3), vec![vec![false; 3]]);
+        assert!(get_row_placements(&[3], 2).is_empty());
+    }
+
+    #[test]
+    fn test_oracle_small_picross() {
+        let picross = PicrossBoard::picross_from_clue_string("T1,1L1,1").unwrap();
+        assert_eq!(find_oracle_solutions(&picross, 10).len(), 2);
+
+        let picross = PicrossBoard::picross_from_clue_string("T2,2L1,1").unwrap();
+        assert!(find_oracle_solutions(&picross, 10).is_empty());
+
+        let picross = PicrossBoard::picross_from_clue_string(CLUE_STRING_5X5).unwrap();
+        let solutions = find_oracle_solutions(&picross, 10);
+        assert_eq!(solutions.len(), 1);
+    }
+}
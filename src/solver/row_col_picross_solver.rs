@@ -1,114 +1,354 @@
 use super::*;
 
+use std::collections::VecDeque;
+use std::sync::Arc;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+//Rows and columns are views over a single grid, the same way ImageLine works over ImageBoard
+#[derive(Clone)]
 pub struct RowColPicross {
-    pub rows: Vec<PicrossLineUnit>,
-    pub cols: Vec<PicrossLineUnit>,
+    pub row_clues: Vec<ClueLine>,
+    pub col_clues: Vec<ClueLine>,
+    pub image: ImageBoard,
     pub line_solve_method: LineSolveMethod,
+    pub contradiction: Option<Contradiction>,
+    //Deductions are only recorded when a trace is given
+    pub trace: Option<SolveTrace>,
+    pub iteration_count: usize,
+    pub line_solve_count: usize,
+    //Shared with the branches of a search and possibly with other picross
+    pub line_cache: Option<Arc<LineCache>>,
+    //Unlimited by default, clones share the tries already spent
+    pub budget: SolveBudget,
+    pub observer: Option<SharedSolveObserver>,
+    //Lines with cells that changed since they were last solved, each line queued once
+    dirty_lines: VecDeque<(Orientation, usize)>,
+    queued_rows: Vec<bool>,
+    queued_cols: Vec<bool>,
+    //The lines of a pass are solved concurrently, the results are applied in order
+    #[cfg(feature = "parallel")]
+    pub parallel: bool,
 }
 
-fn get_rows_from_picross(picross : & PicrossBoard) -> Vec<PicrossLineUnit> {
-    let height = picross.height();
-    let width = picross.width();
-    let mut rows: Vec<PicrossLineUnit> = Vec::with_capacity(height);
-    for i in 0..height { 
-        rows.push(PicrossLineUnit::new(width, picross.left.get_line_copy(i)));
-    }
-    return rows;
+//A line solved on its own, not yet written in the grid
+pub struct LineSolveResult {
+    pub solve: Solve,
+    pub before: Vec<CaseState>,
+    pub line: PicrossLineUnit,
 }
 
-fn get_cols_from_picross(picross : & PicrossBoard) -> Vec<PicrossLineUnit> {
-    let height = picross.height();
-    let width = picross.width();
-    let mut cols: Vec<PicrossLineUnit> = Vec::with_capacity(width);
-    for i in 0..width { 
-        cols.push(PicrossLineUnit::new(height, picross.top.get_line_copy(i)));
-    }
-    return cols;
+pub fn solve_picross_board(picross : &mut PicrossBoard, method : LineSolveMethod) -> Solve {
+    solve_row_col_picross(picross, RowColPicross::new(picross), method)
 }
 
-fn get_solve_combination(line_solve: Solve, global_solve: Solve) -> Solve {
-    match (global_solve, line_solve) {
-        (Solve::None, Solve::None) => Solve::None,
-        (Solve::Full, Solve::Full) => Solve::Full,
-        (Solve::Unknown, _ ) => line_solve,
-        _ => Solve::Partial
-    }
+//The cache can be kept between calls to reuse the lines solved for previous picross
+pub fn solve_picross_board_with_cache(picross : &mut PicrossBoard, method : LineSolveMethod, cache : & Arc<LineCache>) -> Solve {
+    let mut row_col_picross = RowColPicross::new(picross);
+    row_col_picross.line_cache = Some(Arc::clone(cache));
+    solve_row_col_picross(picross, row_col_picross, method)
 }
 
-pub fn solve_picross_board(picross : &mut PicrossBoard, method : LineSolveMethod) -> Solve {
-    let mut solver = RowColPicross::new(&picross);
-    solver.line_solve_method = method;
+//Stops with Exhausted once the options run out, the cells found so far are kept in the picross
+pub fn solve_picross_board_with_options(picross : &mut PicrossBoard, method : LineSolveMethod, options : & SolveOptions) -> Solve {
+    let mut row_col_picross = RowColPicross::new(picross);
+    row_col_picross.budget = SolveBudget::new(options.clone());
+    solve_row_col_picross(picross, row_col_picross, method)
+}
+
+//The picross is solved with a search when line logic is not enough, the image is copied back in the board
+pub fn solve_row_col_picross(picross : &mut PicrossBoard, mut row_col_picross : RowColPicross, method : LineSolveMethod) -> Solve {
+    row_col_picross.line_solve_method = method;
+    let mut solver = BacktrackSolver::new(row_col_picross);
     let solve = solver.solve();
     match solve {
-        Solve::Partial | Solve::Full => {
-            for x in 0..solver.cols.len() {
-                for y in 0..solver.rows.len() {
-                    picross.image.set_value(x,y,solver.rows[y].values[x]);
-                }
-            }
+        Solve::Partial | Solve::Full | Solve::Exhausted => {
+            picross.image = solver.picross.to_image_board();
         }
         Solve::Unknown => {
             panic!("Solve shouln't end as unknown");
         }
         _ => {}
     }
-    return solve;
+    solve
 }
 
 impl RowColPicross{
     pub fn new(picross : & PicrossBoard) -> Self {
-        Self {
-            rows : get_rows_from_picross(picross),
-            cols : get_cols_from_picross(picross),
+        let height = picross.height();
+        let width = picross.width();
+        let mut row_col_picross = Self {
+            row_clues : (0..height).map(|i| picross.left.get_line_copy(i)).collect(),
+            col_clues : (0..width).map(|i| picross.top.get_line_copy(i)).collect(),
+            image : ImageBoard::new_empty(height, width),
             line_solve_method : LineSolveMethod::BrutForce,
+            contradiction : None,
+            trace : None,
+            iteration_count : 0,
+            line_solve_count : 0,
+            line_cache : None,
+            budget : SolveBudget::default(),
+            observer : None,
+            dirty_lines : VecDeque::new(),
+            queued_rows : vec![false; height],
+            queued_cols : vec![false; width],
+            #[cfg(feature = "parallel")]
+            parallel : true,
+        };
+        for i in 0..height {
+            row_col_picross.mark_dirty(Orientation::Horizontal, i);
+        }
+        for i in 0..width {
+            row_col_picross.mark_dirty(Orientation::Vertical, i);
+        }
+        row_col_picross
+    }
+
+    pub fn height(&self) -> usize {
+        self.image.height()
+    }
+
+    pub fn width(&self) -> usize {
+        self.image.width()
+    }
+
+    pub fn mark_dirty(&mut self, orientation : Orientation, idx : usize) {
+        let queued = match orientation {
+            Orientation::Horizontal => &mut self.queued_rows[idx],
+            Orientation::Vertical => &mut self.queued_cols[idx],
+        };
+        if !*queued {
+            *queued = true;
+            self.dirty_lines.push_back((orientation, idx));
         }
     }
 
-    pub fn merge_rows_and_cols(&mut self) {
-        for r in 0..self.rows.len() {
-            for c in 0..self.cols.len() {
-                match (self.rows[r].values[c], self.cols[c].values[r]) {
-                    (_, CaseState::UNKNOWN) => self.cols[c].values[r] = self.rows[r].values[c],
-                    (CaseState::UNKNOWN,_) => self.rows[r].values[c] = self.cols[c].values[r],
-                    (_,_) => {
-                        if self.rows[r].values[c] != self.cols[c].values[r] {
-                            panic!("Horizontal Solve and Vertical Solve give incompatible result");
-                        }
+    pub fn has_dirty_lines(&self) -> bool {
+        !self.dirty_lines.is_empty()
+    }
+
+    pub fn get_value(&self, x : usize, y : usize) -> CaseState {
+        self.image.get_value(x, y)
+    }
+
+    pub fn set_value(&mut self, x : usize, y : usize, val : CaseState) {
+        self.image.set_value(x, y, val);
+        self.mark_dirty(Orientation::Horizontal, y);
+        self.mark_dirty(Orientation::Vertical, x);
+    }
+
+    pub fn get_row(&self, idx : usize) -> ImageLine<'_> {
+        self.image.get_row(idx)
+    }
+
+    pub fn get_col(&self, idx : usize) -> ImageLine<'_> {
+        self.image.get_col(idx)
+    }
+
+    pub fn get_clues(&self, orientation : Orientation, idx : usize) -> &ClueLine {
+        match orientation {
+            Orientation::Horizontal => &self.row_clues[idx],
+            Orientation::Vertical => &self.col_clues[idx],
+        }
+    }
+
+    //A copy of the line the line solvers can work on
+    pub fn get_line_unit(&self, orientation : Orientation, idx : usize) -> PicrossLineUnit {
+        let line = match orientation {
+            Orientation::Horizontal => self.get_row(idx),
+            Orientation::Vertical => self.get_col(idx),
+        };
+        let mut line_unit = PicrossLineUnit::new(line.size(), self.get_clues(orientation, idx).to_vec());
+        for i in 0..line.size() {
+            line_unit.set_value(i, line.get(i));
+        }
+        line_unit
+    }
+
+    pub fn to_image_board(&self) -> ImageBoard {
+        self.image.clone()
+    }
+
+    pub fn unknown_count(&self) -> usize {
+        let mut count = 0;
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                count += (self.get_value(x, y) == CaseState::UNKNOWN) as usize;
+            }
+        }
+        count
+    }
+
+    //Rows do not share cells with each other, neither do columns, so the lines of one orientation can be solved independently
+    pub fn compute_line_solve(&self, orientation : Orientation, idx : usize) -> LineSolveResult {
+        let mut line = self.get_line_unit(orientation, idx);
+        let before = line.values.clone();
+        let mut solver = LineUnitSolver::new(&mut line);
+        solver.solve_method = self.line_solve_method;
+        solver.cache = self.line_cache.as_deref();
+        solver.budget = Some(&self.budget);
+        let solve = solver.solve();
+        LineSolveResult { solve, before, line }
+    }
+
+    //The new cells are written in the grid and make the crossing lines dirty
+    pub fn apply_line_solve(&mut self, orientation : Orientation, idx : usize, result : LineSolveResult) -> Solve {
+        let LineSolveResult { solve, before, line } = result;
+        if solve == Solve::Exhausted {
+            //The line is left untouched and solved again if the solve is resumed
+            self.mark_dirty(orientation, idx);
+            return solve;
+        }
+        self.line_solve_count += 1;
+        if solve == Solve::Contradiction {
+            let contradiction = Contradiction::Line(orientation, idx);
+            self.contradiction = Some(contradiction);
+            notify_observer(&self.observer, |observer| observer.contradiction(contradiction));
+            return solve;
+        }
+        notify_observer(&self.observer, |observer| observer.line_solved(orientation, idx, &before, &line.values));
+
+        if let Some(trace) = self.trace.as_mut() {
+            trace.record_line(orientation, idx, &line.clues, &before, &line.values);
+        }
+        for (i, value) in line.values.iter().enumerate() {
+            if before[i] != *value {
+                match orientation {
+                    Orientation::Horizontal => {
+                        self.image.set_value(i, idx, *value);
+                        self.mark_dirty(Orientation::Vertical, i);
+                        notify_observer(&self.observer, |observer| observer.cell_decided(i, idx, *value));
                     }
+                    Orientation::Vertical => {
+                        self.image.set_value(idx, i, *value);
+                        self.mark_dirty(Orientation::Horizontal, i);
+                        notify_observer(&self.observer, |observer| observer.cell_decided(idx, i, *value));
+                    }
+                }
+            }
+        }
+        solve
+    }
+
+    pub fn solve_line(&mut self, orientation : Orientation, idx : usize) -> Solve {
+        let result = self.compute_line_solve(orientation, idx);
+        self.apply_line_solve(orientation, idx, result)
+    }
+
+    //The lines a pass did not reach stay dirty
+    fn requeue_lines(&mut self, orientation : Orientation, indices : & [usize]) {
+        for &idx in indices {
+            match orientation {
+                Orientation::Horizontal => self.queued_rows[idx] = false,
+                Orientation::Vertical => self.queued_cols[idx] = false,
+            }
+            self.mark_dirty(orientation, idx);
+        }
+    }
+
+    #[cfg(feature = "parallel")]
+    fn compute_line_solves(&self, orientation : Orientation, indices : & [usize]) -> Vec<LineSolveResult> {
+        if self.parallel {
+            indices.par_iter().map(|&idx| self.compute_line_solve(orientation, idx)).collect()
+        } else {
+            indices.iter().map(|&idx| self.compute_line_solve(orientation, idx)).collect()
+        }
+    }
+
+    //Results are applied in index order, stopping at the first contradiction like the sequential solve
+    #[cfg(feature = "parallel")]
+    fn solve_pass(&mut self, orientation : Orientation, indices : Vec<usize>) -> Solve {
+        if self.budget.is_exhausted(0) {
+            self.requeue_lines(orientation, &indices);
+            return Solve::Exhausted;
+        }
+        let results = self.compute_line_solves(orientation, &indices);
+        for (i, result) in results.into_iter().enumerate() {
+            let idx = indices[i];
+            match orientation {
+                Orientation::Horizontal => self.queued_rows[idx] = false,
+                Orientation::Vertical => self.queued_cols[idx] = false,
+            }
+            match self.apply_line_solve(orientation, idx, result) {
+                Solve::Contradiction => return Solve::Contradiction,
+                Solve::Exhausted => {
+                    self.requeue_lines(orientation, &indices[i + 1..]);
+                    return Solve::Exhausted;
+                }
+                _ => {}
+            }
+        }
+        Solve::Partial
+    }
+
+    #[cfg(not(feature = "parallel"))]
+    fn solve_pass(&mut self, orientation : Orientation, indices : Vec<usize>) -> Solve {
+        for (i, &idx) in indices.iter().enumerate() {
+            match orientation {
+                Orientation::Horizontal => self.queued_rows[idx] = false,
+                Orientation::Vertical => self.queued_cols[idx] = false,
+            }
+            match self.solve_line(orientation, idx) {
+                Solve::Contradiction => return Solve::Contradiction,
+                Solve::Exhausted => {
+                    self.requeue_lines(orientation, &indices[i + 1..]);
+                    return Solve::Exhausted;
                 }
+                _ => {}
             }
         }
+        Solve::Partial
     }
 
+    //Only the dirty lines are solved, rows first then columns
+    //Stops at the first line that cannot be solved and keeps it in contradiction
     pub fn solve_iteration(&mut self) -> Solve {
-        let mut current_solve = Solve::Unknown;
-        for row in self.rows.iter_mut() {
-            let mut solver = LineUnitSolver::new(row);
-            if self.line_solve_method == LineSolveMethod::SpaceDistribution { solver.solve_method = LineSolveMethod::SpaceDistribution; }
-            current_solve = get_solve_combination(solver.solve(), current_solve);
+        let mut dirty_rows : Vec<usize> = Vec::new();
+        let mut dirty_cols : Vec<usize> = Vec::new();
+        for (orientation, idx) in self.dirty_lines.drain(..) {
+            match orientation {
+                Orientation::Horizontal => dirty_rows.push(idx),
+                Orientation::Vertical => dirty_cols.push(idx),
+            }
+        }
+        dirty_rows.sort_unstable();
+        dirty_cols.sort_unstable();
+
+        match self.solve_pass(Orientation::Horizontal, dirty_rows) {
+            Solve::Partial => {},
+            Solve::Exhausted => {
+                self.requeue_lines(Orientation::Vertical, &dirty_cols);
+                return Solve::Exhausted;
+            }
+            solve => return solve,
         }
-        for col in self.cols.iter_mut() {
-            let mut solver = LineUnitSolver::new(col);
-            if self.line_solve_method == LineSolveMethod::SpaceDistribution { solver.solve_method = LineSolveMethod::SpaceDistribution; }
-            current_solve = get_solve_combination(solver.solve(), current_solve);
+        match self.solve_pass(Orientation::Vertical, dirty_cols) {
+            Solve::Partial => {},
+            solve => return solve,
+        }
+        match self.unknown_count() {
+            0 => Solve::Full,
+            _ => Solve::Partial,
         }
-        return current_solve;
     }
 }
 
 impl Solvable for RowColPicross {
     fn solve(&mut self) -> Solve {
-        let mut global_solve = Solve::Unknown;
-        loop {
-            let iter_solve = self.solve_iteration();
-            match (global_solve, iter_solve) {
-                (Solve::Unknown, Solve::None)    => return Solve::None,
-                (Solve::Partial, Solve::None)    => return Solve::Partial,
-                (_             , Solve::Partial) => { global_solve = Solve::Partial; }
-                (_             , Solve::Full)    => return Solve::Full,
-                (_,_) => panic!("Impossible solve state"),
+        //Line logic alone cannot go further once there is no dirty line left
+        while self.has_dirty_lines() {
+            self.iteration_count += 1;
+            let iteration = self.iteration_count;
+            notify_observer(&self.observer, |observer| observer.iteration_started(iteration));
+            match self.solve_iteration() {
+                Solve::Contradiction => return Solve::Contradiction,
+                Solve::Exhausted => return Solve::Exhausted,
+                _ => {}
             }
-            self.merge_rows_and_cols();
+        }
+        match self.unknown_count() {
+            0 => Solve::Full,
+            _ => Solve::Partial,
         }
     }
 }
@@ -117,157 +357,223 @@ impl Solvable for RowColPicross {
 mod tests {
     use super::*;
 
-    use std::panic;
-
     #[test]
     fn test_row_col_picross() {
-        let mut picross_str = "T1,2L2,1".to_string();
-        let picross_base = PicrossBoard::picross_from_clue_string(&mut picross_str);
+        let picross_str = "T1,2L2,1".to_string();
+        let picross_base = PicrossBoard::picross_from_clue_string(&picross_str).unwrap();
         assert_eq!(picross_base.height(), 2);
         assert_eq!(picross_base.width(), 2);
         let picross = RowColPicross::new(&picross_base);
-        assert_eq!(picross.rows.len(), 2);
-        assert_eq!(picross.cols.len(), 2);
-        assert_eq!(picross.rows[0].clues.len(), 1);
-        assert_eq!(picross.rows[0].clues[0], 2);
-        assert_eq!(picross.rows[1].clues.len(), 1);
-        assert_eq!(picross.rows[1].clues[0], 1);
-        assert_eq!(picross.cols[0].clues.len(), 1);
-        assert_eq!(picross.cols[0].clues[0], 1);
-        assert_eq!(picross.cols[1].clues.len(), 1);
-        assert_eq!(picross.cols[1].clues[0], 2);
+        assert_eq!(picross.height(), 2);
+        assert_eq!(picross.width(), 2);
+        assert_eq!(picross.row_clues[0], vec![2]);
+        assert_eq!(picross.row_clues[1], vec![1]);
+        assert_eq!(picross.col_clues[0], vec![1]);
+        assert_eq!(picross.col_clues[1], vec![2]);
     }
 
     #[test]
     fn test_rowcolpicross_empty_solve_iteration() {
-        let mut picross_str = "T0,0L0,0".to_string();
-        let picross_base = PicrossBoard::picross_from_clue_string(&mut picross_str);
+        let picross_str = "T0,0L0,0".to_string();
+        let picross_base = PicrossBoard::picross_from_clue_string(&picross_str).unwrap();
         let mut picross = RowColPicross::new(&picross_base);
         assert!(picross.solve_iteration() == Solve::Full);
-        assert!(picross.rows[0].values[0] == CaseState::OFF);
-        assert!(picross.rows[0].values[1] == CaseState::OFF);
-        assert!(picross.rows[1].values[0] == CaseState::OFF);
-        assert!(picross.rows[1].values[1] == CaseState::OFF);
-        assert!(picross.cols[0].values[0] == CaseState::OFF);
-        assert!(picross.cols[0].values[1] == CaseState::OFF);
-        assert!(picross.cols[1].values[0] == CaseState::OFF);
-        assert!(picross.cols[1].values[1] == CaseState::OFF);
+        assert!(picross.get_value(0, 0) == CaseState::OFF);
+        assert!(picross.get_value(1, 0) == CaseState::OFF);
+        assert!(picross.get_value(0, 1) == CaseState::OFF);
+        assert!(picross.get_value(1, 1) == CaseState::OFF);
+        assert!(picross.get_col(0).get(0) == CaseState::OFF);
+        assert!(picross.get_col(0).get(1) == CaseState::OFF);
+        assert!(picross.get_col(1).get(0) == CaseState::OFF);
+        assert!(picross.get_col(1).get(1) == CaseState::OFF);
     }
 
     #[test]
     fn test_rowcolpicross_full_solve_iteration() {
-        let mut picross_str = "T2,2L2,2".to_string();
-        let picross_base = PicrossBoard::picross_from_clue_string(&mut picross_str);
+        let picross_str = "T2,2L2,2".to_string();
+        let picross_base = PicrossBoard::picross_from_clue_string(&picross_str).unwrap();
         let mut picross = RowColPicross::new(&picross_base);
         assert!(picross.solve_iteration() == Solve::Full);
-        assert!(picross.rows[0].values[0] == CaseState::ON);
-        assert!(picross.rows[0].values[1] == CaseState::ON);
-        assert!(picross.rows[1].values[0] == CaseState::ON);
-        assert!(picross.rows[1].values[1] == CaseState::ON);
-        assert!(picross.cols[0].values[0] == CaseState::ON);
-        assert!(picross.cols[0].values[1] == CaseState::ON);
-        assert!(picross.cols[1].values[0] == CaseState::ON);
-        assert!(picross.cols[1].values[1] == CaseState::ON);
+        assert!(picross.get_value(0, 0) == CaseState::ON);
+        assert!(picross.get_value(1, 0) == CaseState::ON);
+        assert!(picross.get_value(0, 1) == CaseState::ON);
+        assert!(picross.get_value(1, 1) == CaseState::ON);
+        assert!(picross.get_col(0).get(0) == CaseState::ON);
+        assert!(picross.get_col(0).get(1) == CaseState::ON);
+        assert!(picross.get_col(1).get(0) == CaseState::ON);
+        assert!(picross.get_col(1).get(1) == CaseState::ON);
     }
 
     #[test]
     fn test_rowcolpicross_solve_iteration_on_already_solved() {
-        let mut picross_str = "T1L1".to_string();
-        let picross_base = PicrossBoard::picross_from_clue_string(&mut picross_str);
+        let picross_str = "T1L1".to_string();
+        let picross_base = PicrossBoard::picross_from_clue_string(&picross_str).unwrap();
         let mut picross = RowColPicross::new(&picross_base);
-        picross.rows[0].values[0] = CaseState::ON;
-        picross.cols[0].values[0] = CaseState::ON;
+        picross.set_value(0, 0, CaseState::ON);
         assert!(picross.solve_iteration() == Solve::Full);
-        assert!(picross.rows[0].values[0] == CaseState::ON);
-        assert!(picross.cols[0].values[0] == CaseState::ON);
+        assert!(picross.get_value(0, 0) == CaseState::ON);
+        assert!(picross.get_col(0).get(0) == CaseState::ON);
     }
 
     #[test]
     fn test_rowcolpicross_solve_iteration_complex() {
-        let mut picross_str = "T1,1,2L11,2".to_string();
-        let picross_base = PicrossBoard::picross_from_clue_string(&mut picross_str);
+        let picross_str = "T1,1,2L11,2".to_string();
+        let picross_base = PicrossBoard::picross_from_clue_string(&picross_str).unwrap();
         let mut picross = RowColPicross::new(&picross_base);
-        assert!(picross.solve_iteration() == Solve::Partial);
-
-        assert!(picross.rows[0].values[0] == CaseState::ON);
-        assert!(picross.rows[0].values[1] == CaseState::OFF);
-        assert!(picross.rows[0].values[2] == CaseState::ON);
-        assert!(picross.rows[1].values[0] == CaseState::UNKNOWN);
-        assert!(picross.rows[1].values[1] == CaseState::ON);
-        assert!(picross.rows[1].values[2] == CaseState::UNKNOWN);
-        assert!(picross.cols[0].values[0] == CaseState::UNKNOWN);
-        assert!(picross.cols[0].values[1] == CaseState::UNKNOWN);
-        assert!(picross.cols[1].values[0] == CaseState::UNKNOWN);
-        assert!(picross.cols[1].values[1] == CaseState::UNKNOWN);
-        assert!(picross.cols[2].values[0] == CaseState::ON);
-        assert!(picross.cols[2].values[1] == CaseState::ON);
+        //The columns are solved with what the rows found in the same iteration
+        assert!(picross.solve_iteration() == Solve::Full);
+
+        assert!(picross.get_value(0, 0) == CaseState::ON);
+        assert!(picross.get_value(1, 0) == CaseState::OFF);
+        assert!(picross.get_value(2, 0) == CaseState::ON);
+        assert!(picross.get_value(0, 1) == CaseState::OFF);
+        assert!(picross.get_value(1, 1) == CaseState::ON);
+        assert!(picross.get_value(2, 1) == CaseState::ON);
+        assert!(picross.get_col(2).get(0) == CaseState::ON);
+        assert!(picross.get_col(2).get(1) == CaseState::ON);
     }
 
     #[test]
-    fn test_rowcolpicross_usefull_merge() {
-        let mut picross_str = "T0L0,0".to_string();
-        let picross_base = PicrossBoard::picross_from_clue_string(&mut picross_str);
+    fn test_rowcolpicross_shared_grid() {
+        let picross_str = "T0L0,0".to_string();
+        let picross_base = PicrossBoard::picross_from_clue_string(&picross_str).unwrap();
         let mut picross = RowColPicross::new(&picross_base);
-        
-        picross.rows[0].values[0] = CaseState::ON;
-        picross.cols[0].values[1] = CaseState::OFF;
-        assert!(picross.cols[0].values[0] == CaseState::UNKNOWN);
-        assert!(picross.rows[1].values[0] == CaseState::UNKNOWN);
-        picross.merge_rows_and_cols();
-        assert!(picross.cols[0].values[0] == CaseState::ON);
-        assert!(picross.rows[1].values[0] == CaseState::OFF);
+
+        picross.set_value(0, 1, CaseState::OFF);
+        assert!(picross.get_row(1).get(0) == CaseState::OFF);
+        assert!(picross.get_col(0).get(1) == CaseState::OFF);
+        assert!(picross.get_col(0).get(0) == CaseState::UNKNOWN);
+
+        let line_unit = picross.get_line_unit(Orientation::Vertical, 0);
+        assert_eq!(line_unit.clues, vec![0]);
+        assert_eq!(line_unit.values, vec![CaseState::UNKNOWN, CaseState::OFF]);
     }
 
-    
     #[test]
-    fn test_rowcolpicross_merge_with_same_value() {
-        let mut picross_str = "T0L0".to_string();
-        let picross_base = PicrossBoard::picross_from_clue_string(&mut picross_str);
+    fn test_solve_line_contradiction() {
+        let picross_base = PicrossBoard::picross_from_clue_string("T2,2L1,1").unwrap();
         let mut picross = RowColPicross::new(&picross_base);
+        assert!(picross.solve() == Solve::Contradiction);
+        assert_eq!(picross.contradiction, Some(Contradiction::Line(Orientation::Horizontal, 0)));
 
-        picross.rows[0].values[0] = CaseState::ON;
-        picross.cols[0].values[0] = CaseState::ON;
-        picross.merge_rows_and_cols();
-        assert!(picross.rows[0].values[0] == CaseState::ON);
-        assert!(picross.cols[0].values[0] == CaseState::ON);
+        let picross_base = PicrossBoard::picross_from_clue_string("T3L1").unwrap();
+        let mut picross = RowColPicross::new(&picross_base);
+        picross.line_solve_method = LineSolveMethod::SpaceDistribution;
+        assert!(picross.solve() == Solve::Contradiction);
+        assert_eq!(picross.contradiction, Some(Contradiction::Line(Orientation::Vertical, 0)));
     }
 
     #[test]
-    #[should_panic]
-    fn test_rowcolpicross_merge_panic() {
-        let mut picross_str = "T0L0".to_string();
-        let picross_base = PicrossBoard::picross_from_clue_string(&mut picross_str);
+    fn test_solve_complex() {
+        let picross_str = "T1,1,2L11,2".to_string();
+        let picross_base = PicrossBoard::picross_from_clue_string(&picross_str).unwrap();
         let mut picross = RowColPicross::new(&picross_base);
+        assert!(picross.solve() == Solve::Full);
 
-        picross.rows[0].values[0] = CaseState::ON;
-        picross.cols[0].values[0] = CaseState::OFF;
+        assert!(picross.get_value(0, 0) == CaseState::ON);
+        assert!(picross.get_value(1, 0) == CaseState::OFF);
+        assert!(picross.get_value(2, 0) == CaseState::ON);
+        assert!(picross.get_value(0, 1) == CaseState::OFF);
+        assert!(picross.get_value(1, 1) == CaseState::ON);
+        assert!(picross.get_value(2, 1) == CaseState::ON);
 
-        panic::set_hook(Box::new(|_| {
-            println!("Intended Panic Happened");
-        }));
+        assert!(picross.get_col(0).get(0) == CaseState::ON);
+        assert!(picross.get_col(0).get(1) == CaseState::OFF);
+        assert!(picross.get_col(1).get(0) == CaseState::OFF);
+        assert!(picross.get_col(1).get(1) == CaseState::ON);
+        assert!(picross.get_col(2).get(0) == CaseState::ON);
+        assert!(picross.get_col(2).get(1) == CaseState::ON);
+    }
+
+    #[test]
+    fn test_only_dirty_lines_are_solved() {
+        let picross_base = PicrossBoard::picross_from_clue_string(CLUE_STRING_30X30).unwrap();
+        let mut picross = RowColPicross::new(&picross_base);
+        picross.line_solve_method = LineSolveMethod::Dynamic;
+        assert!(picross.solve() == Solve::Full);
+        assert!(!picross.has_dirty_lines());
+
+        let line_count = picross_base.height() + picross_base.width();
+        assert!(picross.iteration_count > 1);
+        assert!(picross.line_solve_count < picross.iteration_count * line_count);
+
+        //Nothing changed, so nothing to solve again
+        let line_solve_count = picross.line_solve_count;
+        assert!(picross.solve() == Solve::Full);
+        assert_eq!(picross.line_solve_count, line_solve_count);
 
-        picross.merge_rows_and_cols();
+        picross.set_value(0, 0, picross.get_value(0, 0));
+        assert!(picross.solve() == Solve::Full);
+        assert_eq!(picross.line_solve_count, line_solve_count + 2);
     }
 
+    #[cfg(feature = "parallel")]
     #[test]
-    fn test_solve_complex() {
-        let mut picross_str = "T1,1,2L11,2".to_string();
-        let picross_base = PicrossBoard::picross_from_clue_string(&mut picross_str);
+    fn test_parallel_solve_is_deterministic() {
+        let mut picross_list = vec![PicrossBoard::picross_from_clue_string(CLUE_STRING_30X30).unwrap(), PicrossBoard::picross_from_clue_string(CLUE_STRING_21X21).unwrap()];
+        for seed in 0..5 {
+            picross_list.push(PicrossBoard::new_from_image(&create_random_image_board(40, 50, seed)));
+        }
+        for picross_base in picross_list.iter() {
+            let mut results = Vec::new();
+            for &parallel in [false, true].iter() {
+                let mut picross = RowColPicross::new(picross_base);
+                picross.line_solve_method = LineSolveMethod::Dynamic;
+                picross.parallel = parallel;
+                picross.trace = Some(SolveTrace::new());
+                let solve = picross.solve();
+                results.push((solve, picross.to_image_board(), picross.contradiction, picross.trace, picross.line_solve_count, picross.iteration_count));
+            }
+            assert!(results[0] == results[1]);
+        }
+    }
+
+    #[test]
+    fn test_solve_with_line_cache() {
+        let cache = Arc::new(LineCache::new(10000));
+        let mut picross = PicrossBoard::picross_from_clue_string(CLUE_STRING_20X20).unwrap();
+        assert!(solve_picross_board_with_cache(&mut picross, LineSolveMethod::Dynamic, &cache) == Solve::Full);
+        assert!(validate_picross(&mut picross));
+        let first_stats = cache.stats();
+        assert!(first_stats.misses > 0);
+
+        //The same picross again only needs the cached lines
+        let mut picross = PicrossBoard::picross_from_clue_string(CLUE_STRING_20X20).unwrap();
+        assert!(solve_picross_board_with_cache(&mut picross, LineSolveMethod::BrutForce, &cache) == Solve::Full);
+        assert!(validate_picross(&mut picross));
+        let second_stats = cache.stats();
+        assert_eq!(second_stats.misses, first_stats.misses);
+        assert!(second_stats.hits > first_stats.hits);
+        assert_eq!(second_stats.size, first_stats.size);
+    }
+
+    #[test]
+    fn test_solve_budget_line_tries() {
+        let picross_base = PicrossBoard::picross_from_clue_string(CLUE_STRING_15X15).unwrap();
         let mut picross = RowColPicross::new(&picross_base);
+        picross.budget = SolveBudget::new(SolveOptions::new().with_max_line_tries(1000));
+        assert!(picross.solve() == Solve::Exhausted);
+        assert!(picross.budget.line_tries() > 0);
+        assert!(picross.has_dirty_lines());
+
+        //The lines not solved stay dirty, so the solve can be resumed with a new budget
+        picross.budget = SolveBudget::default();
         assert!(picross.solve() == Solve::Full);
+    }
+
+    #[test]
+    fn test_solve_budget_cancel_and_timeout() {
+        let picross_base = PicrossBoard::picross_from_clue_string(CLUE_STRING_15X15).unwrap();
+        let cancel = Arc::new(std::sync::atomic::AtomicBool::new(true));
+        let mut picross = RowColPicross::new(&picross_base);
+        picross.budget = SolveBudget::new(SolveOptions::new().with_cancel_flag(cancel));
+        assert!(picross.solve() == Solve::Exhausted);
+        assert_eq!(picross.unknown_count(), 15 * 15);
+        assert_eq!(picross.line_solve_count, 0);
 
-        assert!(picross.rows[0].values[0] == CaseState::ON);
-        assert!(picross.rows[0].values[1] == CaseState::OFF);
-        assert!(picross.rows[0].values[2] == CaseState::ON);
-        assert!(picross.rows[1].values[0] == CaseState::OFF);
-        assert!(picross.rows[1].values[1] == CaseState::ON);
-        assert!(picross.rows[1].values[2] == CaseState::ON);
-
-        assert!(picross.cols[0].values[0] == CaseState::ON);
-        assert!(picross.cols[0].values[1] == CaseState::OFF);
-        assert!(picross.cols[1].values[0] == CaseState::OFF);
-        assert!(picross.cols[1].values[1] == CaseState::ON);
-        assert!(picross.cols[2].values[0] == CaseState::ON);
-        assert!(picross.cols[2].values[1] == CaseState::ON);
-    }
-}
\ No newline at end of file
+        let mut picross = PicrossBoard::picross_from_clue_string(CLUE_STRING_15X15).unwrap();
+        let options = SolveOptions::new().with_timeout(std::time::Duration::from_secs(0));
+        assert!(solve_picross_board_with_options(&mut picross, LineSolveMethod::Dynamic, &options) == Solve::Exhausted);
+        assert!(solve_picross_board_with_options(&mut picross, LineSolveMethod::Dynamic, &SolveOptions::new()) == Solve::Full);
+    }
+}
@@ -0,0 +1,232 @@
+use super::*;
+
+use std::fmt::Write;
+
+//Clauses are lists of DIMACS literals, a variable v is the literal v when true and -v when false
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct CnfFormula {
+    pub variable_count: usize,
+    pub clauses: Vec<Vec<i64>>,
+}
+
+//The cells are the first variables, row by row, true meaning ON
+pub fn cell_variable(width : usize, x : usize, y : usize) -> i64 {
+    (y * width + x + 1) as i64
+}
+
+impl CnfFormula {
+    fn new_variable(&mut self) -> i64 {
+        self.variable_count += 1;
+        self.variable_count as i64
+    }
+
+    //One auxiliary variable per possible start of each block, blocks are kept in order and apart,
+    //a cell is ON if and only if a block covers it
+    fn add_line(&mut self, clues : & ClueLine, cells : & [i64]) {
+        let blocks : Vec<usize> = clues.iter().copied().filter(|&c| c > 0).collect();
+        let size = cells.len();
+        let total = match get_min_line_size(&blocks) {
+            Some(total) if total <= size => total,
+            _ => {
+                self.clauses.push(Vec::new());
+                return;
+            }
+        };
+
+        let mut starts : Vec<Vec<(usize, i64)>> = Vec::with_capacity(blocks.len());
+        let mut min_start = 0;
+        for &block in blocks.iter() {
+            let max_start = min_start + size - total;
+            let block_starts : Vec<(usize, i64)> = (min_start..=max_start).map(|p| (p, self.new_variable())).collect();
+            min_start += block + 1;
+
+            self.clauses.push(block_starts.iter().map(|&(_, var)| var).collect());
+            for (i, &(_, first)) in block_starts.iter().enumerate() {
+                for &(_, second) in block_starts[i + 1..].iter() {
+                    self.clauses.push(vec![-first, -second]);
+                }
+            }
+            for &(p, var) in block_starts.iter() {
+                for cell in cells[p..p + block].iter() {
+                    self.clauses.push(vec![-var, *cell]);
+                }
+            }
+            starts.push(block_starts);
+        }
+
+        for j in 1..blocks.len() {
+            for &(p, previous) in starts[j - 1].iter() {
+                for &(q, next) in starts[j].iter() {
+                    if q <= p + blocks[j - 1] {
+                        self.clauses.push(vec![-previous, -next]);
+                    }
+                }
+            }
+        }
+
+        for (c, cell) in cells.iter().enumerate() {
+            let mut clause = vec![-cell];
+            for (j, block_starts) in starts.iter().enumerate() {
+                for &(p, var) in block_starts.iter() {
+                    if p <= c && c < p + blocks[j] {
+                        clause.push(var);
+                    }
+                }
+            }
+            self.clauses.push(clause);
+        }
+    }
+
+    pub fn to_dimacs(&self) -> String {
+        let mut text = String::new();
+        writeln!(text, "p cnf {} {}", self.variable_count, self.clauses.len()).ok();
+        for clause in self.clauses.iter() {
+            for literal in clause.iter() {
+                write!(text, "{} ", literal).ok();
+            }
+            writeln!(text, "0").ok();
+        }
+        text
+    }
+}
+
+pub fn encode_picross_cnf(picross : & PicrossBoard) -> CnfFormula {
+    let height = picross.height();
+    let width = picross.width();
+    let mut formula = CnfFormula { variable_count: height * width, clauses: Vec::new() };
+    for y in 0..height {
+        let cells : Vec<i64> = (0..width).map(|x| cell_variable(width, x, y)).collect();
+        formula.add_line(&picross.left.get_line_copy(y), &cells);
+    }
+    for x in 0..width {
+        let cells : Vec<i64> = (0..height).map(|y| cell_variable(width, x, y)).collect();
+        formula.add_line(&picross.top.get_line_copy(x), &cells);
+    }
+    formula
+}
+
+pub fn export_picross_dimacs(picross : & PicrossBoard) -> String {
+    let mut text = String::new();
+    writeln!(text, "c picross {}x{}", picross.width(), picross.height()).ok();
+    writeln!(text, "c cell (x, y) is variable y * {} + x + 1, true when ON", picross.width()).ok();
+    text.push_str(&encode_picross_cnf(picross).to_dimacs());
+    text
+}
+
+//Reads the output of a SAT solver, either plain literals or competition style "s" and "v" lines
+//Auxiliary variables are ignored, cells missing from the model stay UNKNOWN
+pub fn image_from_sat_model(model : & str, height : usize, width : usize) -> Option<ImageBoard> {
+    let mut image = ImageBoard::new_empty(height, width);
+    for line in model.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('c') || line == "SAT" || line == "s SATISFIABLE" {
+            continue;
+        }
+        if line.contains("UNSAT") {
+            return None;
+        }
+        let literals = line.strip_prefix('v').unwrap_or(line);
+        for literal in literals.split_whitespace() {
+            let literal : i64 = literal.parse().ok()?;
+            let var = literal.unsigned_abs() as usize;
+            if literal == 0 || var > height * width {
+                continue;
+            }
+            let value = if literal > 0 { CaseState::ON } else { CaseState::OFF };
+            image.set_value((var - 1) % width, (var - 1) / width, value);
+        }
+    }
+    Some(image)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //Plain backtracking with unit propagation, enough for small formulas
+    fn solve_cnf(clauses : & [Vec<i64>], assignment : &mut Vec<Option<bool>>) -> bool {
+        loop {
+            let mut unit = None;
+            for clause in clauses.iter() {
+                let mut unassigned = Vec::new();
+                let mut satisfied = false;
+                for &literal in clause.iter() {
+                    match assignment[literal.unsigned_abs() as usize] {
+                        Some(value) => satisfied |= value == (literal > 0),
+                        None => unassigned.push(literal),
+                    }
+                }
+                if satisfied {
+                    continue;
+                }
+                match unassigned.len() {
+                    0 => return false,
+                    1 => { unit = Some(unassigned[0]); break; }
+                    _ => {}
+                }
+            }
+            match unit {
+                Some(literal) => assignment[literal.unsigned_abs() as usize] = Some(literal > 0),
+                None => break,
+            }
+        }
+        let var = match (1..assignment.len()).find(|&v| assignment[v].is_none()) {
+            Some(var) => var,
+            None => return true,
+        };
+        for &value in [true, false].iter() {
+            let mut branch = assignment.clone();
+            branch[var] = Some(value);
+            if solve_cnf(clauses, &mut branch) {
+                *assignment = branch;
+                return true;
+            }
+        }
+        false
+    }
+
+    fn model_to_string(assignment : & [Option<bool>]) -> String {
+        let literals : Vec<String> = (1..assignment.len())
+            .map(|v| if assignment[v] == Some(false) { format!("-{}", v) } else { format!("{}", v) })
+            .collect();
+        format!("s SATISFIABLE\nv {} 0\n", literals.join(" "))
+    }
+
+    #[test]
+    fn test_cnf_solution_matches_solver() {
+        for clue_string in [CLUE_STRING_5X5, CLUE_STRING_6X6, "T1,1L1,1", "T0,2,0L1,1"].iter() {
+            let mut picross = PicrossBoard::picross_from_clue_string(clue_string).unwrap();
+            let formula = encode_picross_cnf(&picross);
+            let mut assignment = vec![None; formula.variable_count + 1];
+            assert!(solve_cnf(&formula.clauses, &mut assignment));
+
+            let image = image_from_sat_model(&model_to_string(&assignment), picross.height(), picross.width()).unwrap();
+            picross.image = image;
+            assert!(validate_picross(&mut picross));
+        }
+    }
+
+    #[test]
+    fn test_cnf_unsatisfiable() {
+        let picross = PicrossBoard::picross_from_clue_string("T2,2L1,1").unwrap();
+        let formula = encode_picross_cnf(&picross);
+        let mut assignment = vec![None; formula.variable_count + 1];
+        assert!(!solve_cnf(&formula.clauses, &mut assignment));
+
+        let picross = PicrossBoard::picross_from_clue_string("T3L1,1").unwrap();
+        assert!(encode_picross_cnf(&picross).clauses.contains(&Vec::new()));
+        assert!(image_from_sat_model("s UNSATISFIABLE\n", 2, 1).is_none());
+    }
+
+    #[test]
+    fn test_dimacs_format() {
+        let picross = PicrossBoard::picross_from_clue_string("T1L1").unwrap();
+        let text = export_picross_dimacs(&picross);
+        assert!(text.starts_with("c picross 1x1\n"));
+        assert!(text.contains("p cnf 3 "));
+
+        let image = image_from_sat_model("SAT\n-1 2 0\n", 1, 1).unwrap();
+        assert!(image.get_value(0, 0) == CaseState::OFF);
+        assert!(image_from_sat_model("v 1 x 0", 1, 1).is_none());
+    }
+}
@@ -0,0 +1,151 @@
+use super::*;
+
+use std::cmp;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SolutionCount {
+    None,
+    Unique,
+    Multiple,
+}
+
+pub struct SolutionCounter {
+    pub picross: RowColPicross,
+    pub limit: usize,
+    pub solutions: Vec<ImageBoard>,
+    //The search stopped on the budget of the picross, the solutions found may not be all of them
+    pub exhausted: bool,
+}
+
+impl SolutionCounter {
+    //At least one solution is looked for, a limit of 0 would report a solvable picross as impossible
+    pub fn new(picross : RowColPicross, limit : usize) -> Self {
+        Self {
+            picross,
+            limit: cmp::max(limit, 1),
+            solutions: Vec::new(),
+            exhausted: false,
+        }
+    }
+
+    //Both branches of every guess are explored, until enough solutions are found
+    fn search(&mut self, mut picross : RowColPicross, depth : usize) {
+        if self.solutions.len() >= self.limit || self.exhausted {
+            return;
+        }
+
+        match picross.solve() {
+            Solve::Full => {
+                self.solutions.push(picross.to_image_board());
+                return;
+            }
+            Solve::Partial => {},
+            Solve::Exhausted => {
+                self.exhausted = true;
+                return;
+            }
+            _ => return,
+        }
+
+        if let Some((x, y)) = choose_unknown_cell(&picross) {
+            for &state in [CaseState::ON, CaseState::OFF].iter() {
+                let mut branch = picross.clone();
+                branch.set_value(x, y, state);
+                notify_observer(&picross.observer, |observer| observer.branch_pushed(x, y, state, depth + 1));
+                self.search(branch, depth + 1);
+                notify_observer(&picross.observer, |observer| observer.branch_popped(depth + 1));
+            }
+        }
+    }
+
+    pub fn find_solutions(&mut self) -> &Vec<ImageBoard> {
+        self.solutions.clear();
+        self.exhausted = false;
+        self.search(self.picross.clone(), 0);
+        &self.solutions
+    }
+
+    pub fn get_solution_count(&self) -> SolutionCount {
+        match self.solutions.len() {
+            0 => SolutionCount::None,
+            1 => SolutionCount::Unique,
+            _ => SolutionCount::Multiple,
+        }
+    }
+}
+
+//Stops the search after limit solutions, the boards returned are all distinct
+pub fn find_picross_solutions(picross : & PicrossBoard, method : LineSolveMethod, limit : usize) -> Vec<ImageBoard> {
+    let mut row_col_picross = RowColPicross::new(picross);
+    row_col_picross.line_solve_method = method;
+    let mut counter = SolutionCounter::new(row_col_picross, limit);
+    counter.find_solutions();
+    counter.solutions
+}
+
+pub fn count_picross_solutions(picross : & PicrossBoard, method : LineSolveMethod) -> SolutionCount {
+    let mut row_col_picross = RowColPicross::new(picross);
+    row_col_picross.line_solve_method = method;
+    let mut counter = SolutionCounter::new(row_col_picross, 2);
+    counter.find_solutions();
+    counter.get_solution_count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count_unique_picross() {
+        let picross = PicrossBoard::picross_from_clue_string("T1,3,1L1,3,1").unwrap();
+        assert!(count_picross_solutions(&picross, LineSolveMethod::Dynamic) == SolutionCount::Unique);
+
+        let solutions = find_picross_solutions(&picross, LineSolveMethod::Dynamic, 10);
+        assert_eq!(solutions.len(), 1);
+        assert_eq!(solutions[0].get_value(1, 1), CaseState::ON);
+        assert_eq!(solutions[0].get_value(0, 0), CaseState::OFF);
+    }
+
+    #[test]
+    fn test_count_ambiguous_picross() {
+        let picross = PicrossBoard::picross_from_clue_string("T1,1L1,1").unwrap();
+        assert!(count_picross_solutions(&picross, LineSolveMethod::Dynamic) == SolutionCount::Multiple);
+
+        let solutions = find_picross_solutions(&picross, LineSolveMethod::Dynamic, 10);
+        assert_eq!(solutions.len(), 2);
+        assert!(solutions[0] != solutions[1]);
+
+        let solutions = find_picross_solutions(&picross, LineSolveMethod::Dynamic, 1);
+        assert_eq!(solutions.len(), 1);
+    }
+
+    #[test]
+    fn test_count_with_zero_limit() {
+        let picross = PicrossBoard::picross_from_clue_string("T1,3,1L1,3,1").unwrap();
+        let mut counter = SolutionCounter::new(RowColPicross::new(&picross), 0);
+        assert_eq!(counter.limit, 1);
+        assert_eq!(counter.find_solutions().len(), 1);
+        assert!(counter.get_solution_count() == SolutionCount::Unique);
+        assert_eq!(find_picross_solutions(&picross, LineSolveMethod::Dynamic, 0).len(), 1);
+    }
+
+    #[test]
+    fn test_count_impossible_picross() {
+        let picross = PicrossBoard::picross_from_clue_string("T2,2L1,1").unwrap();
+        assert!(count_picross_solutions(&picross, LineSolveMethod::Dynamic) == SolutionCount::None);
+        assert!(find_picross_solutions(&picross, LineSolveMethod::Dynamic, 10).is_empty());
+    }
+
+    #[test]
+    fn test_found_solutions_are_valid() {
+        let image = create_random_image_board(6, 6, 3);
+        let picross = PicrossBoard::new_from_image(&image);
+        let solutions = find_picross_solutions(&picross, LineSolveMethod::Dynamic, 100);
+        assert!(solutions.contains(&image));
+        for solution in solutions {
+            let mut solved = PicrossBoard::new_from_image(&image);
+            solved.image = solution;
+            assert!(validate_picross(&mut solved));
+        }
+    }
+}
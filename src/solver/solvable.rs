@@ -1,12 +1,38 @@
+use super::*;
+
 #[repr(u8)]
 #[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "lowercase"))]
 pub enum Solve {
     None,
     Partial,
     Full,
     Unknown,
+    Contradiction,
+    //The solve budget ran out before the end, see SolveOptions
+    Exhausted,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "lowercase"))]
+pub enum Contradiction {
+    //No arrangement of the clues fits the row (Horizontal) or column (Vertical) at this index
+    Line(Orientation, usize),
 }
 
 pub trait Solvable {
     fn solve(&mut self) -> Solve;
-}
\ No newline at end of file
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_solve_json() {
+        assert_eq!(serde_json::to_string(&Solve::Exhausted).unwrap(), "\"exhausted\"");
+        assert_eq!(serde_json::from_str::<Solve>("\"full\"").unwrap(), Solve::Full);
+        let contradiction = Contradiction::Line(Orientation::Horizontal, 3);
+        assert_eq!(serde_json::to_string(&contradiction).unwrap(), r#"{"line":["horizontal",3]}"#);
+    }
+}
@@ -0,0 +1,112 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+//Inside a line the budget is only checked every few tries
+pub const BUDGET_CHECK_PERIOD : usize = 256;
+
+#[derive(Clone, Default, Debug)]
+pub struct SolveOptions {
+    pub deadline: Option<Instant>,
+    //Sum of the tries of every line solve, see LineUnitSolver::count
+    pub max_line_tries: Option<usize>,
+    //Set it from another thread to stop the solve between two lines
+    pub cancel: Option<Arc<AtomicBool>>,
+}
+
+impl SolveOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_timeout(mut self, timeout : Duration) -> Self {
+        self.deadline = Some(Instant::now() + timeout);
+        self
+    }
+
+    pub fn with_max_line_tries(mut self, max_line_tries : usize) -> Self {
+        self.max_line_tries = Some(max_line_tries);
+        self
+    }
+
+    pub fn with_cancel_flag(mut self, cancel : Arc<AtomicBool>) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
+}
+
+//Clones share the same try counter, so the branches of a search use a single budget
+#[derive(Clone, Default, Debug)]
+pub struct SolveBudget {
+    pub options: SolveOptions,
+    line_tries: Arc<AtomicUsize>,
+}
+
+impl SolveBudget {
+    pub fn new(options : SolveOptions) -> Self {
+        Self {
+            options,
+            line_tries: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    pub fn line_tries(&self) -> usize {
+        self.line_tries.load(Ordering::Relaxed)
+    }
+
+    pub fn add_line_tries(&self, tries : usize) {
+        self.line_tries.fetch_add(tries, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        match &self.options.cancel {
+            Some(cancel) => cancel.load(Ordering::Relaxed),
+            None => false,
+        }
+    }
+
+    //pending_tries are the tries of a line solve not added yet
+    pub fn is_exhausted(&self, pending_tries : usize) -> bool {
+        if let Some(max_line_tries) = self.options.max_line_tries {
+            if self.line_tries() + pending_tries > max_line_tries {
+                return true;
+            }
+        }
+        if let Some(deadline) = self.options.deadline {
+            if Instant::now() >= deadline {
+                return true;
+            }
+        }
+        self.is_cancelled()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unlimited_budget() {
+        let budget = SolveBudget::default();
+        budget.add_line_tries(usize::MAX / 2);
+        assert!(!budget.is_exhausted(1000));
+    }
+
+    #[test]
+    fn test_budget_limits() {
+        let budget = SolveBudget::new(SolveOptions::new().with_max_line_tries(10));
+        assert!(!budget.is_exhausted(10));
+        assert!(budget.is_exhausted(11));
+        budget.clone().add_line_tries(5);
+        assert!(budget.is_exhausted(6));
+
+        let budget = SolveBudget::new(SolveOptions::new().with_timeout(Duration::from_secs(0)));
+        assert!(budget.is_exhausted(0));
+
+        let cancel = Arc::new(AtomicBool::new(false));
+        let budget = SolveBudget::new(SolveOptions::new().with_cancel_flag(Arc::clone(&cancel)));
+        assert!(!budget.is_exhausted(0));
+        cancel.store(true, Ordering::Relaxed);
+        assert!(budget.is_exhausted(0));
+    }
+}
@@ -0,0 +1,93 @@
+use super::*;
+
+use std::sync::{Arc, Mutex};
+
+//Every event does nothing by default, an observer only implements the ones it needs
+pub trait SolveObserver {
+    fn iteration_started(&mut self, _iteration : usize) {}
+    fn line_solved(&mut self, _orientation : Orientation, _idx : usize, _before : & [CaseState], _after : & [CaseState]) {}
+    fn cell_decided(&mut self, _x : usize, _y : usize, _value : CaseState) {}
+    fn contradiction(&mut self, _contradiction : Contradiction) {}
+    //A guess of the search, depth is the depth of the new branch
+    fn branch_pushed(&mut self, _x : usize, _y : usize, _value : CaseState, _depth : usize) {}
+    fn branch_popped(&mut self, _depth : usize) {}
+}
+
+//Shared by the picross and its search branches, the caller keeps its own handle to read the observer after the solve
+pub type SharedSolveObserver = Arc<Mutex<dyn SolveObserver + Send>>;
+
+pub fn notify_observer<F : FnOnce(&mut dyn SolveObserver)>(observer : & Option<SharedSolveObserver>, event : F) {
+    if let Some(observer) = observer {
+        event(&mut *observer.lock().unwrap());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct EventCounter {
+        iterations: usize,
+        lines: usize,
+        cells: usize,
+        contradictions: usize,
+        pushed: usize,
+        popped: usize,
+    }
+
+    impl SolveObserver for EventCounter {
+        fn iteration_started(&mut self, _iteration : usize) { self.iterations += 1; }
+        fn line_solved(&mut self, _orientation : Orientation, _idx : usize, before : & [CaseState], after : & [CaseState]) {
+            assert_eq!(before.len(), after.len());
+            self.lines += 1;
+        }
+        fn cell_decided(&mut self, _x : usize, _y : usize, value : CaseState) {
+            assert!(value != CaseState::UNKNOWN);
+            self.cells += 1;
+        }
+        fn contradiction(&mut self, _contradiction : Contradiction) { self.contradictions += 1; }
+        fn branch_pushed(&mut self, _x : usize, _y : usize, _value : CaseState, _depth : usize) { self.pushed += 1; }
+        fn branch_popped(&mut self, _depth : usize) { self.popped += 1; }
+    }
+
+    #[test]
+    fn test_observer_line_logic_events() {
+        let picross_base = PicrossBoard::picross_from_clue_string(CLUE_STRING_15X15).unwrap();
+        let counter = Arc::new(Mutex::new(EventCounter::default()));
+        let mut picross = RowColPicross::new(&picross_base);
+        picross.line_solve_method = LineSolveMethod::Dynamic;
+        picross.observer = Some(counter.clone());
+        assert!(picross.solve() == Solve::Full);
+
+        let counter = counter.lock().unwrap();
+        assert_eq!(counter.iterations, picross.iteration_count);
+        assert_eq!(counter.lines, picross.line_solve_count);
+        assert_eq!(counter.cells, 15 * 15);
+        assert_eq!(counter.contradictions, 0);
+        assert_eq!(counter.pushed, 0);
+    }
+
+    #[test]
+    fn test_observer_search_events() {
+        let picross_base = PicrossBoard::picross_from_clue_string("T1,1L1,1").unwrap();
+        let counter = Arc::new(Mutex::new(EventCounter::default()));
+        let mut picross = RowColPicross::new(&picross_base);
+        picross.observer = Some(counter.clone());
+        let mut solver = BacktrackSolver::new(picross);
+        assert!(solver.solve() == Solve::Full);
+
+        let counter = counter.lock().unwrap();
+        assert!(counter.pushed >= 1);
+        assert_eq!(counter.pushed, counter.popped);
+        //The guessed cell is only reported by branch_pushed
+        assert_eq!(counter.cells + counter.pushed, 4);
+
+        let picross_base = PicrossBoard::picross_from_clue_string("T2,2L1,1").unwrap();
+        let counter = Arc::new(Mutex::new(EventCounter::default()));
+        let mut picross = RowColPicross::new(&picross_base);
+        picross.observer = Some(counter.clone());
+        assert!(picross.solve() == Solve::Contradiction);
+        assert_eq!(counter.lock().unwrap().contradictions, 1);
+    }
+}
@@ -0,0 +1,341 @@
+use super::*;
+
+use std::fmt;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum StepReason {
+    //The cells are ON in every placement of this block
+    Overlap(usize),
+    //The cells border a run of ON cells that can only be this block, already at its full size
+    BlockComplete(usize),
+    //No block can reach the cells
+    OutOfReach,
+    //The line has no block at all
+    EmptyLine,
+    //Every placement of the whole line agrees on the cells
+    Combination,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct CellChange {
+    pub x: usize,
+    pub y: usize,
+    pub value: CaseState,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct SolveStep {
+    pub orientation: Orientation,
+    pub index: usize,
+    pub changes: Vec<CellChange>,
+    pub reason: StepReason,
+}
+
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct SolveTrace {
+    pub steps: Vec<SolveStep>,
+}
+
+struct BlockReach {
+    size: usize,
+    first_start: usize,
+    last_start: usize,
+}
+
+fn get_block_reaches(placements : & LinePlacements, clues : & ClueLine, size : usize) -> Vec<BlockReach> {
+    let blocks : Vec<usize> = clues.iter().copied().filter(|&c| c > 0).collect();
+    let mut reaches = Vec::with_capacity(blocks.len());
+    for (j, &block) in blocks.iter().enumerate() {
+        if block > size {
+            return Vec::new();
+        }
+        let starts : Vec<usize> = (0..=(size - block)).filter(|&s| placements.can_start_at(j, s)).collect();
+        if let (Some(&first_start), Some(&last_start)) = (starts.first(), starts.last()) {
+            reaches.push(BlockReach { size: block, first_start, last_start });
+        }
+    }
+    reaches
+}
+
+fn get_on_run(values : & [CaseState], idx : usize, backward : bool) -> Option<(usize, usize)> {
+    let mut start = idx;
+    let mut end = idx;
+    if backward {
+        while start > 0 && values[start - 1] == CaseState::ON {
+            start -= 1;
+        }
+    } else {
+        while end < values.len() && values[end] == CaseState::ON {
+            end += 1;
+        }
+    }
+    match backward {
+        true if start < idx => Some((start, idx)),
+        false if end > idx => Some((idx, end)),
+        _ => None,
+    }
+}
+
+fn explain_off_cell(placements : & LinePlacements, reaches : & [BlockReach], before : & [CaseState], idx : usize) -> StepReason {
+    if reaches.is_empty() {
+        return StepReason::EmptyLine;
+    }
+    let runs = [get_on_run(before, idx, true), get_on_run(before, idx + 1, false)];
+    for &(start, end) in runs.iter().flatten() {
+        let candidates : Vec<usize> = (0..reaches.len()).filter(|&j| {
+            let lowest = end.saturating_sub(reaches[j].size);
+            reaches[j].size >= end - start && (lowest..=start).any(|s| placements.can_start_at(j, s))
+        }).collect();
+        if !candidates.is_empty() && candidates.iter().all(|&j| reaches[j].size == end - start) {
+            return StepReason::BlockComplete(candidates[0]);
+        }
+    }
+    if !reaches.iter().any(|r| idx >= r.first_start && idx < r.last_start + r.size) {
+        return StepReason::OutOfReach;
+    }
+    StepReason::Combination
+}
+
+//Why each cell known in after but not in before got its value, grouped by reason
+pub fn explain_line_changes(clues : & ClueLine, before : & [CaseState], after : & [CaseState]) -> Vec<(StepReason, Vec<usize>)> {
+    let placements = LinePlacements::new(clues, before);
+    let reaches = get_block_reaches(&placements, clues, before.len());
+    let mut groups : Vec<(StepReason, Vec<usize>)> = Vec::new();
+    for idx in 0..before.len() {
+        if before[idx] != CaseState::UNKNOWN || after[idx] == CaseState::UNKNOWN {
+            continue;
+        }
+        let reason = match after[idx] {
+            CaseState::ON => match reaches.iter().position(|r| idx >= r.last_start && idx < r.first_start + r.size) {
+                Some(j) => StepReason::Overlap(j),
+                None => StepReason::Combination,
+            },
+            _ => explain_off_cell(&placements, &reaches, before, idx),
+        };
+        match groups.iter_mut().find(|(r, _)| *r == reason) {
+            Some((_, cells)) => cells.push(idx),
+            None => groups.push((reason, vec![idx])),
+        }
+    }
+    groups
+}
+
+impl SolveTrace {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    //Line changes become steps, positions are turned into board coordinates
+    pub fn record_line(&mut self, orientation : Orientation, index : usize, clues : & ClueLine, before : & [CaseState], after : & [CaseState]) {
+        for (reason, cells) in explain_line_changes(clues, before, after) {
+            let changes = cells.iter().map(|&i| {
+                let (x, y) = match orientation {
+                    Orientation::Horizontal => (i, index),
+                    Orientation::Vertical => (index, i),
+                };
+                CellChange { x, y, value: after[i] }
+            }).collect();
+            self.steps.push(SolveStep { orientation, index, changes, reason });
+        }
+    }
+
+    pub fn replay(&self, image : &mut ImageBoard) {
+        for step in self.steps.iter() {
+            step.apply(image);
+        }
+    }
+
+    //One step per line : orientation, index, reason, then the changed cells as x,y,value
+    pub fn serialize(&self) -> String {
+        let mut text = String::new();
+        for step in self.steps.iter() {
+            let orientation = match step.orientation {
+                Orientation::Horizontal => "H",
+                Orientation::Vertical => "V",
+            };
+            let reason = match step.reason {
+                StepReason::Overlap(j) => format!("overlap:{}", j),
+                StepReason::BlockComplete(j) => format!("complete:{}", j),
+                StepReason::OutOfReach => "reach".to_string(),
+                StepReason::EmptyLine => "empty".to_string(),
+                StepReason::Combination => "combination".to_string(),
+            };
+            text.push_str(&format!("{} {} {}", orientation, step.index, reason));
+            for change in step.changes.iter() {
+                let value = match change.value {
+                    CaseState::ON => "1",
+                    CaseState::OFF => "0",
+                    CaseState::UNKNOWN => "?",
+                };
+                text.push_str(&format!(" {},{},{}", change.x, change.y, value));
+            }
+            text.push('\n');
+        }
+        text
+    }
+
+    pub fn deserialize(text : & str) -> Option<Self> {
+        let mut trace = Self::new();
+        for line in text.lines().filter(|l| !l.trim().is_empty()) {
+            let mut tokens = line.split_whitespace();
+            let orientation = match tokens.next()? {
+                "H" => Orientation::Horizontal,
+                "V" => Orientation::Vertical,
+                _ => return None,
+            };
+            let index = tokens.next()?.parse().ok()?;
+            let mut reason_parts = tokens.next()?.split(':');
+            let reason = match (reason_parts.next()?, reason_parts.next()) {
+                ("overlap", Some(j)) => StepReason::Overlap(j.parse().ok()?),
+                ("complete", Some(j)) => StepReason::BlockComplete(j.parse().ok()?),
+                ("reach", None) => StepReason::OutOfReach,
+                ("empty", None) => StepReason::EmptyLine,
+                ("combination", None) => StepReason::Combination,
+                _ => return None,
+            };
+            let mut changes = Vec::new();
+            for token in tokens {
+                let fields : Vec<&str> = token.split(',').collect();
+                if fields.len() != 3 {
+                    return None;
+                }
+                let value = match fields[2] {
+                    "1" => CaseState::ON,
+                    "0" => CaseState::OFF,
+                    "?" => CaseState::UNKNOWN,
+                    _ => return None,
+                };
+                changes.push(CellChange { x: fields[0].parse().ok()?, y: fields[1].parse().ok()?, value });
+            }
+            trace.steps.push(SolveStep { orientation, index, changes, reason });
+        }
+        Some(trace)
+    }
+}
+
+impl StepReason {
+    //How hard the deduction is for a player, 0 being the easiest
+    pub fn difficulty(&self) -> usize {
+        match self {
+            StepReason::EmptyLine => 0,
+            StepReason::Overlap(_) => 1,
+            StepReason::BlockComplete(_) => 2,
+            StepReason::OutOfReach => 3,
+            StepReason::Combination => 4,
+        }
+    }
+}
+
+impl SolveStep {
+    pub fn apply(&self, image : &mut ImageBoard) {
+        for change in self.changes.iter() {
+            image.set_value(change.x, change.y, change.value);
+        }
+    }
+}
+
+//Blocks and cells are numbered from 1 for the players
+impl fmt::Display for StepReason {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StepReason::Overlap(j) => write!(f, "overlap of block {}", j + 1),
+            StepReason::BlockComplete(j) => write!(f, "block {} already complete", j + 1),
+            StepReason::OutOfReach => write!(f, "no block can reach these cells"),
+            StepReason::EmptyLine => write!(f, "the line is empty"),
+            StepReason::Combination => write!(f, "every possible arrangement agrees"),
+        }
+    }
+}
+
+impl fmt::Display for SolveStep {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        let (line, positions) : (&str, Vec<usize>) = match self.orientation {
+            Orientation::Horizontal => ("Row", self.changes.iter().map(|c| c.x).collect()),
+            Orientation::Vertical => ("Column", self.changes.iter().map(|c| c.y).collect()),
+        };
+        write!(f, "{} {}:", line, self.index + 1)?;
+        for (position, change) in positions.iter().zip(self.changes.iter()) {
+            let value = match change.value {
+                CaseState::ON => "filled",
+                CaseState::OFF => "empty",
+                CaseState::UNKNOWN => "unknown",
+            };
+            write!(f, " cell {} {},", position + 1, value)?;
+        }
+        write!(f, " {}", self.reason)
+    }
+}
+
+impl fmt::Display for SolveTrace {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        for (i, step) in self.steps.iter().enumerate() {
+            writeln!(f, "{:>3}. {}", i + 1, step)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::image::CaseState::*;
+
+    #[test]
+    fn test_explain_overlap() {
+        let before = vec![UNKNOWN; 9];
+        let mut after = before.to_vec();
+        solve_line_dynamic(&vec![2,2,2], &mut after);
+        assert_eq!(explain_line_changes(&vec![2,2,2], &before, &after), vec![
+            (StepReason::Overlap(0), vec![1]),
+            (StepReason::Overlap(1), vec![4]),
+            (StepReason::Overlap(2), vec![7]),
+        ]);
+    }
+
+    #[test]
+    fn test_explain_off_cells() {
+        let before = vec![UNKNOWN, ON, ON, UNKNOWN, UNKNOWN, UNKNOWN];
+        let mut after = before.to_vec();
+        solve_line_dynamic(&vec![2], &mut after);
+        assert_eq!(explain_line_changes(&vec![2], &before, &after), vec![
+            (StepReason::BlockComplete(0), vec![0, 3]),
+            (StepReason::OutOfReach, vec![4, 5]),
+        ]);
+
+        let before = vec![UNKNOWN; 3];
+        let after = vec![OFF; 3];
+        assert_eq!(explain_line_changes(&vec![0], &before, &after), vec![(StepReason::EmptyLine, vec![0, 1, 2])]);
+    }
+
+    #[test]
+    fn test_trace_serialization() {
+        let mut trace = SolveTrace::new();
+        trace.record_line(Orientation::Vertical, 2, &vec![2], &[UNKNOWN, ON, UNKNOWN, UNKNOWN], &[UNKNOWN, ON, UNKNOWN, OFF]);
+        trace.record_line(Orientation::Horizontal, 0, &vec![0], &[UNKNOWN; 2], &[OFF; 2]);
+        assert_eq!(trace.steps[0].changes, vec![CellChange { x: 2, y: 3, value: OFF }]);
+        assert_eq!(trace.steps[0].to_string(), "Column 3: cell 4 empty, no block can reach these cells");
+
+        let text = trace.serialize();
+        assert_eq!(text, "V 2 reach 2,3,0\nH 0 empty 0,0,0 1,0,0\n");
+        assert!(SolveTrace::deserialize(&text) == Some(trace));
+        assert!(SolveTrace::deserialize("D 0 reach").is_none());
+    }
+
+    #[test]
+    fn test_trace_replay() {
+        let picross_base = PicrossBoard::picross_from_clue_string(CLUE_STRING_10X10).unwrap();
+        let mut picross = RowColPicross::new(&picross_base);
+        picross.trace = Some(SolveTrace::new());
+        assert!(picross.solve() == Solve::Full);
+
+        let trace = picross.trace.as_ref().unwrap();
+        let mut image = ImageBoard::new_empty(picross_base.height(), picross_base.width());
+        trace.replay(&mut image);
+        assert!(image == picross.to_image_board());
+
+        //Each cell is deduced once
+        let change_count : usize = trace.steps.iter().map(|s| s.changes.len()).sum();
+        assert_eq!(change_count, picross_base.height() * picross_base.width());
+    }
+}
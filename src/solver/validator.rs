@@ -1,78 +1,29 @@
 use super::*;
 
 pub fn validate_line(clues : & ClueLine, line : & ImageLine) -> bool {
-    let nb_clues = clues.len();
-    let mut current_clue : usize = 0;
-    let mut current_block_size : usize = 0;
-    for i in 0..line.size() {
-        match line.get(i) {
-            CaseState::ON => { //Start or continuing a block
-                //Check if a clue remains
-                if current_clue == nb_clues {
-                    return false;
-                }
-                
-                //Then increase block size
-                current_block_size += 1;
-
-                //Check if block is already too big
-                if current_block_size > clues[current_clue] {
-                    return false;
-                }
-            },
-            _ => {
-                if current_block_size > 0 {
-
-                    //Check if terminated block has good size
-                    if current_block_size != clues[current_clue] {
-                        return false;
-                    }
-
-                    //Go to next clue and reset block size
-                    current_clue += 1;
-                    current_block_size = 0;
-                }
-            }
-        }
-    }
-    //Finish last block
-    if current_block_size > 0 {
-        if current_block_size != clues[current_clue] {
-            return false;
-        }
-        current_clue += 1;
-    }
-
-    //Check if we have validated all clues
-    if (current_clue != nb_clues) && (clues[current_clue] != 0) {
-        return false;
-    }
+    validate_packed_line(clues, &PackedLine::from_image_line(line))
+}
 
-    true
+//The runs of ON cells are read word by word and have to be the clues, UNKNOWN cells count as OFF
+pub fn validate_packed_line(clues : & ClueLine, line : & PackedLine) -> bool {
+    let runs = line.get_on_runs();
+    let run_count = runs.len();
+    run_count <= clues.len() && runs[..] == clues[..run_count] && (run_count == clues.len() || clues[run_count] == 0)
 }
 
 pub fn validate_row(picross : & mut PicrossBoard, idx : usize) -> bool{
-    validate_line(&picross.left.get_line(idx), &picross.image.get_row(idx))
+    validate_line(picross.left.get_line(idx), &picross.image.get_row(idx))
 }
 
 pub fn validate_col(picross : & mut PicrossBoard, idx : usize) -> bool{
-    validate_line(&picross.top.get_line(idx), &picross.image.get_col(idx))
+    validate_line(picross.top.get_line(idx), &picross.image.get_col(idx))
 }
 
+//The image is packed once, then every row and column is validated on its masks
 pub fn validate_picross(picross : & mut PicrossBoard) -> bool {
-    let height = picross.height();
-    let width = picross.width();
-    for i in 0..height {
-        if !validate_row(picross, i) {
-            return false;
-        }
-    }
-    for i in 0..width {
-        if !validate_col(picross, i) {
-            return false;
-        }
-    }
-    true
+    let board = PackedBoard::from_image(&picross.image);
+    (0..board.height()).all(|i| validate_packed_line(picross.left.get_line(i), board.get_row(i)))
+        && (0..board.width()).all(|i| validate_packed_line(picross.top.get_line(i), &board.get_col(i)))
 }
 
 #[cfg(test)]
@@ -223,4 +174,32 @@ mod tests {
         let img : ImageBoard = create_one_line_board(&[ON, ON, OFF, ON]);        
         assert!(!validate_line(&clues, &img.get_row(0)));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_packed_validation_matches_own_clues() {
+        //A full line is valid for the clues it creates and only for them
+        let clue_sets : Vec<ClueLine> = vec![vec![0], vec![], vec![2], vec![1,1], vec![2,1], vec![1,3], vec![3,1,1]];
+        for seed in 0..20 {
+            let img = create_random_image_board(1, 7, seed);
+            let packed = PackedLine::from_image_line(&img.get_row(0));
+            let own_clues = PicrossBoard::new_from_image(&img).left.get_line_copy(0);
+            let own_blocks : ClueLine = own_clues.iter().copied().filter(|&c| c > 0).collect();
+            assert!(validate_packed_line(&own_clues, &packed));
+            for clues in clue_sets.iter() {
+                let blocks : ClueLine = clues.iter().copied().filter(|&c| c > 0).collect();
+                assert_eq!(validate_packed_line(clues, &packed), blocks == own_blocks);
+            }
+        }
+    }
+
+    #[test]
+    fn test_picross_validation() {
+        let img = create_random_image_board(70, 90, 5);
+        let mut picross = PicrossBoard::new_from_image(&img);
+        picross.image = img.clone();
+        assert!(validate_picross(&mut picross));
+        let value = picross.image.get_value(80, 65);
+        picross.image.set_value(80, 65, if value == ON { OFF } else { ON });
+        assert!(!validate_picross(&mut picross));
+    }
+}
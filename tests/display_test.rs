@@ -28,7 +28,7 @@ fn display_generated_x_shape_picross() {
 }
 
 fn display_picross_generated_from_string() {
-    let picross = PicrossBoard::picross_from_clue_string(&mut CLUE_STRING_20X20.to_string());
+    let picross = PicrossBoard::picross_from_clue_string(CLUE_STRING_20X20).unwrap();
     picross.display()
 }
 
@@ -37,6 +37,7 @@ mod tests {
     use super::*;
 
     use serial_test::serial;
+    use std::sync::{Arc, Mutex};
 
     #[test]
     #[serial]
@@ -45,20 +46,20 @@ mod tests {
         let img : ImageBoard = create_cross_image_board(size);
         let mut picross : PicrossBoard = PicrossBoard::new_from_image(&img);
         copy_image_in_picross(&mut picross, &img);
-        assert_eq!(validate_picross(&mut picross), true);
+        assert!(validate_picross(&mut picross));
     }
 
     #[test]
     #[serial]
     fn test_validation_fail() {
-        let mut picross = PicrossBoard::picross_from_clue_string(&mut CLUE_STRING_20X20.to_string());
-        assert_eq!(validate_picross(& mut picross), false);
+        let mut picross = PicrossBoard::picross_from_clue_string(CLUE_STRING_20X20).unwrap();
+        assert!(!validate_picross(& mut picross));
     }
 
     #[test]
     #[serial]
     fn sequential_display_tests(){
-        println!("");
+        println!();
         println!("Draw an empty picross");
         display_empty_picross();
         println!("Draw a X shape picross");
@@ -70,24 +71,22 @@ mod tests {
     #[test]
     #[serial]
     fn row_col_picross_display_test(){
-        println!("");
-        let picross = PicrossBoard::picross_from_clue_string(& CLUE_STRING_10X10.to_string());
+        println!();
+        let picross = PicrossBoard::picross_from_clue_string(CLUE_STRING_10X10).unwrap();
         let mut solver = RowColPicross::new(&picross);
         solver.display();
 
         while solver.solve_iteration() != Solve::Full {
-            solver.merge_rows_and_cols();
             solver.display();
         }
 
-        solver.merge_rows_and_cols();
         solver.display();
     }
 
     #[test]
     #[serial]
     fn picross_line_unit_display_test(){
-        println!("");
+        println!();
         let clues: ClueLine = vec![1,1,2,1,1];
         let size: usize = 10;
         let mut picross = PicrossLineUnit::new(size, clues);
@@ -101,7 +100,7 @@ mod tests {
     #[test]
     #[serial]
     fn one_line_solve_partial_display_test(){
-        println!("");
+        println!();
         let clues: ClueLine = vec![6,1];
         let size: usize = 10;
         let mut picross = PicrossLineUnit::new(size, clues);
@@ -114,7 +113,7 @@ mod tests {
     #[test]
     #[serial]
     fn one_line_solve_full_display_test(){
-        println!("");
+        println!();
         let clues: ClueLine = vec![2];
         let size: usize = 3;
         let mut picross = PicrossLineUnit::new(size, clues);
@@ -122,4 +121,39 @@ mod tests {
         solver.verbose = Verbose::Full;
         solver.solve();
     }
-}
\ No newline at end of file
+
+    #[test]
+    #[serial]
+    fn ambiguity_report_display_test(){
+        println!();
+        let picross = PicrossBoard::picross_from_clue_string("T1,1,0,1,1L1,1,0,1,1").unwrap();
+        let report = report_picross_ambiguity(&picross, LineSolveMethod::Dynamic, 10).unwrap();
+        assert!(!report.regions.is_empty());
+        report.display();
+    }
+
+    #[test]
+    #[serial]
+    fn solve_animation_display_test(){
+        println!();
+        let mut picross = PicrossBoard::picross_from_clue_string(CLUE_STRING_10X10).unwrap();
+        let solve = animate_picross_solve(&mut picross, LineSolveMethod::Dynamic, std::time::Duration::from_millis(0));
+        assert!(solve == Solve::Full);
+        assert!(validate_picross(&mut picross));
+
+        let mut picross = PicrossBoard::picross_from_clue_string("T1,1L1,1").unwrap();
+        assert!(animate_picross_solve(&mut picross, LineSolveMethod::Dynamic, std::time::Duration::from_millis(0)) == Solve::Full);
+    }
+
+    #[test]
+    #[serial]
+    fn solve_animation_last_frame_test(){
+        println!();
+        //The solve needs a guess, the animation must not end on the board before it
+        let mut picross = PicrossBoard::picross_from_clue_string("T1,1L1,1").unwrap();
+        let row_col_picross = RowColPicross::new(&picross);
+        let animation = Arc::new(Mutex::new(SolveAnimation::new(&row_col_picross, std::time::Duration::from_millis(0))));
+        assert!(animate_row_col_picross_solve(&mut picross, row_col_picross, &animation, LineSolveMethod::Dynamic) == Solve::Full);
+        assert!(*animation.lock().unwrap().get_image() == picross.image);
+    }
+}
@@ -0,0 +1,57 @@
+extern crate picross_solver;
+
+use crate::picross_solver::solver::*;
+
+//Both solvers must find the same solutions, in any order, unless both stopped on the limit
+fn check_same_solutions(picross : & PicrossBoard, limit : usize) {
+    let oracle_solutions = find_oracle_solutions(picross, limit);
+    let solutions = find_picross_solutions(picross, LineSolveMethod::Dynamic, limit);
+    assert_eq!(oracle_solutions.len(), solutions.len());
+    if solutions.len() < limit {
+        for solution in solutions.iter() {
+            assert!(oracle_solutions.contains(solution));
+        }
+    }
+
+    let mut solved = picross.clone();
+    match solve_picross_board(&mut solved, LineSolveMethod::Dynamic) {
+        Solve::Full => assert!(oracle_solutions.contains(&solved.image)),
+        Solve::Contradiction => assert!(oracle_solutions.is_empty()),
+        solve => panic!("Unexpected solve {:?}", solve),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serial_test::serial;
+
+    #[test]
+    #[serial]
+    fn test_oracle_on_samples() {
+        let samples = [CLUE_STRING_5X5, CLUE_STRING_6X6, CLUE_STRING_7X7, CLUE_STRING_8X8, CLUE_STRING_9X9,
+            CLUE_STRING_10X10, CLUE_STRING_12X12, CLUE_STRING_15X15, CLUE_STRING_16X16, CLUE_STRING_20X20,
+            CLUE_STRING_21X21, CLUE_STRING_25X25];
+        for sample in samples.iter() {
+            check_same_solutions(&PicrossBoard::picross_from_clue_string(sample).unwrap(), 2);
+        }
+    }
+
+    #[test]
+    #[serial]
+    #[ignore = "Too long"]
+    fn test_oracle_on_30x30() {
+        check_same_solutions(&PicrossBoard::picross_from_clue_string(CLUE_STRING_30X30).unwrap(), 2);
+    }
+
+    #[test]
+    #[serial]
+    fn test_oracle_on_random_picross() {
+        for seed in 0..40 {
+            let size = 3 + (seed as usize) % 6;
+            let image = create_random_image_board(size, size + 1, seed);
+            check_same_solutions(&PicrossBoard::new_from_image(&image), 20);
+        }
+    }
+}
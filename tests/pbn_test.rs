@@ -0,0 +1,38 @@
+extern crate picross_solver;
+
+use crate::picross_solver::solver::*;
+
+use std::fs;
+
+//Every webpbn file of tests/data is solved and compared with its goal
+fn get_pbn_test_files() -> Vec<std::path::PathBuf> {
+    let mut paths : Vec<std::path::PathBuf> = fs::read_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data")).unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "xml"))
+        .collect();
+    paths.sort();
+    paths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serial_test::serial;
+
+    #[test]
+    #[serial]
+    fn test_solve_pbn_files() {
+        let paths = get_pbn_test_files();
+        assert!(!paths.is_empty());
+        for path in paths {
+            let text = fs::read_to_string(&path).unwrap();
+            for goal in picross_list_from_pbn_string(&text).unwrap() {
+                let mut picross = goal.clone();
+                picross.image = ImageBoard::new_empty(goal.height(), goal.width());
+                assert!(solve_picross_board(&mut picross, LineSolveMethod::Dynamic) == Solve::Full, "{:?}", path);
+                assert!(picross.image == goal.image, "{:?}", path);
+            }
+        }
+    }
+}
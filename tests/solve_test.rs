@@ -10,9 +10,10 @@ mod tests {
     use serial_test::serial;
 
     fn test_solve_and_display_common(s : &str, method : LineSolveMethod) {
-        println!("");
-        let mut picross = PicrossBoard::picross_from_clue_string(&mut s.to_string());
-        solve_picross_board(&mut picross, method);
+        println!();
+        let mut picross = PicrossBoard::picross_from_clue_string(s).unwrap();
+        assert!(solve_picross_board(&mut picross, method) == Solve::Full);
+        assert!(validate_picross(&mut picross));
         picross.display()
     }
 
@@ -43,4 +44,75 @@ mod tests {
         test_solve_and_display_common(CLUE_STRING_16X16, LineSolveMethod::BrutForce);
         test_solve_and_display_common(CLUE_STRING_20X20, LineSolveMethod::BrutForce);
     }
-}
\ No newline at end of file
+
+    #[test]
+    #[serial]
+    fn test_all_solve_dynamic() {
+        test_solve_and_display_common(CLUE_STRING_5X5, LineSolveMethod::Dynamic);
+        test_solve_and_display_common(CLUE_STRING_6X6, LineSolveMethod::Dynamic);
+        test_solve_and_display_common(CLUE_STRING_7X7, LineSolveMethod::Dynamic);
+        test_solve_and_display_common(CLUE_STRING_8X8, LineSolveMethod::Dynamic);
+        test_solve_and_display_common(CLUE_STRING_9X9, LineSolveMethod::Dynamic);
+        test_solve_and_display_common(CLUE_STRING_10X10, LineSolveMethod::Dynamic);
+        test_solve_and_display_common(CLUE_STRING_12X12, LineSolveMethod::Dynamic);
+        test_solve_and_display_common(CLUE_STRING_15X15, LineSolveMethod::Dynamic);
+        test_solve_and_display_common(CLUE_STRING_16X16, LineSolveMethod::Dynamic);
+        test_solve_and_display_common(CLUE_STRING_20X20, LineSolveMethod::Dynamic);
+        test_solve_and_display_common(CLUE_STRING_25X25, LineSolveMethod::Dynamic);
+        test_solve_and_display_common(CLUE_STRING_30X30, LineSolveMethod::Dynamic);
+    }
+
+    #[test]
+    #[serial]
+    fn test_solve_dynamic_100x100() {
+        //Two random images merged, so that most cells are ON and the search stays short
+        let mut image = create_random_image_board(100, 100, 1);
+        let other = create_random_image_board(100, 100, 101);
+        for y in 0..100 {
+            for x in 0..100 {
+                if other.get_value(x, y) == CaseState::ON {
+                    image.set_value(x, y, CaseState::ON);
+                }
+            }
+        }
+        let mut picross = PicrossBoard::new_from_image(&image);
+        assert!(solve_picross_board(&mut picross, LineSolveMethod::Dynamic) == Solve::Full);
+        assert!(validate_picross(&mut picross));
+    }
+
+    #[test]
+    #[serial]
+    fn test_solve_inconsistent_clues() {
+        //Its rows and columns do not have the same number of filled cells
+        let mut picross = PicrossBoard::picross_from_clue_string(CLUE_STRING_21X21).unwrap();
+        assert!(solve_picross_board(&mut picross, LineSolveMethod::Dynamic) == Solve::Contradiction);
+
+        let mut solver = RowColPicross::new(&picross);
+        solver.line_solve_method = LineSolveMethod::Dynamic;
+        assert!(solver.solve() == Solve::Contradiction);
+        assert!(solver.contradiction.is_some());
+    }
+
+    #[test]
+    #[serial]
+    fn test_solve_clue_longer_than_line() {
+        for method in [LineSolveMethod::BrutForce, LineSolveMethod::SpaceDistribution, LineSolveMethod::Dynamic].iter() {
+            let mut picross = PicrossBoard::picross_from_clue_string("T(9223372036854775809)L1").unwrap();
+            assert!(solve_picross_board(&mut picross, *method) == Solve::Contradiction);
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_samples_have_unique_solution() {
+        let samples = [CLUE_STRING_5X5, CLUE_STRING_6X6, CLUE_STRING_7X7, CLUE_STRING_8X8, CLUE_STRING_9X9, CLUE_STRING_10X10,
+            CLUE_STRING_12X12, CLUE_STRING_15X15, CLUE_STRING_16X16, CLUE_STRING_20X20, CLUE_STRING_25X25, CLUE_STRING_30X30];
+        for s in samples.iter() {
+            let picross = PicrossBoard::picross_from_clue_string(s).unwrap();
+            assert!(count_picross_solutions(&picross, LineSolveMethod::Dynamic) == SolutionCount::Unique);
+        }
+
+        let picross = PicrossBoard::picross_from_clue_string(CLUE_STRING_21X21).unwrap();
+        assert!(count_picross_solutions(&picross, LineSolveMethod::Dynamic) == SolutionCount::None);
+    }
+}